@@ -0,0 +1,1500 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
+use std::f32::consts;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::evaluate::Evaluator;
+use crate::object::{self, ErrorValue, Objects, RuntimeError, SeenStructs};
+
+/// The signature every native function implements. Arguments are checked
+/// against the declared arity before the function is called.
+pub type BuiltinFn = fn(&mut Evaluator, Vec<Objects>) -> Result<Objects, RuntimeError>;
+
+/// How many arguments a builtin accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    Between(usize, usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn check(&self, name: &str, given: usize) -> Result<(), RuntimeError> {
+        let (accepted, expected) = match *self {
+            Arity::Exact(n) => (given == n, format!("{} {}", n, object::arguments(n))),
+            Arity::Between(min, max) => (
+                given >= min && given <= max,
+                format!("{} to {} arguments", min, max),
+            ),
+            Arity::AtLeast(n) => (
+                given >= n,
+                format!("at least {} {}", n, object::arguments(n)),
+            ),
+        };
+        if accepted {
+            return Ok(());
+        }
+        let message = format!("{} expects {} but got {}", name, expected, given);
+        return Err(RuntimeError::new(&message));
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Arity::Exact(n) => write!(f, "{}", n),
+            Arity::Between(min, max) => write!(f, "{}-{}", min, max),
+            Arity::AtLeast(n) => write!(f, "{}+", n),
+        }
+    }
+}
+
+/// A native function together with the metadata shown by the REPL.
+#[derive(Debug, Clone)]
+pub struct BuiltinFunction {
+    pub name: String,
+    pub arity: Arity,
+    pub doc: String,
+    pub function: BuiltinFn,
+}
+
+/// Every native function an evaluator can call, by name. Names defined by
+/// the program shadow the ones registered here.
+#[derive(Debug, Clone, Default)]
+pub struct BuiltinRegistry {
+    functions: BTreeMap<String, BuiltinFunction>,
+    constants: BTreeMap<String, Objects>,
+}
+
+impl BuiltinRegistry {
+    /// Creates a registry holding the standard builtins.
+    pub fn new() -> Self {
+        let mut registry = BuiltinRegistry::default();
+        registry.register("print", Arity::Exact(1), "prints a value", print);
+        registry.register(
+            "len",
+            Arity::Exact(1),
+            "number of elements of a string, array, tuple or hash",
+            len,
+        );
+        registry.register(
+            "first",
+            Arity::Exact(1),
+            "first element of a string or array",
+            first,
+        );
+        registry.register(
+            "last",
+            Arity::Exact(1),
+            "last element of a string or array",
+            last,
+        );
+        registry.register(
+            "push",
+            Arity::Exact(2),
+            "copy of an array with a value appended",
+            push,
+        );
+        registry.register(
+            "compare",
+            Arity::Exact(2),
+            "-1, 0 or 1 ordering two strings or numbers",
+            compare,
+        );
+        registry.register("upper", Arity::Exact(1), "string in upper case", upper);
+        registry.register("lower", Arity::Exact(1), "string in lower case", lower);
+        registry.register("keys", Arity::Exact(1), "array of the keys of a hash", keys);
+        registry.register(
+            "values",
+            Arity::Exact(1),
+            "array of the values of a hash",
+            values,
+        );
+        registry.register(
+            "error",
+            Arity::Between(1, 2),
+            "error value from a message and an optional kind",
+            error,
+        );
+        registry.register("ok", Arity::Exact(1), "ok result holding a value", ok);
+        registry.register("err", Arity::Exact(1), "err result holding a value", err);
+        registry.register(
+            "unwrap",
+            Arity::Exact(1),
+            "value of an ok result, fails on err",
+            unwrap,
+        );
+        registry.register(
+            "unwrap_or",
+            Arity::Exact(2),
+            "value of an ok result, or the default on err",
+            unwrap_or,
+        );
+        registry.register("is_ok", Arity::Exact(1), "whether a result is ok", is_ok);
+        registry.register("is_err", Arity::Exact(1), "whether a result is err", is_err);
+        registry.register(
+            "str",
+            Arity::Exact(1),
+            "string representation of a value",
+            str,
+        );
+        registry.register(
+            "trim",
+            Arity::Exact(1),
+            "string without surrounding whitespace",
+            trim,
+        );
+        registry.register(
+            "split",
+            Arity::Between(1, 2),
+            "array of the parts of a string around a separator, whitespace by default",
+            split,
+        );
+        registry.register(
+            "join",
+            Arity::Between(1, 2),
+            "string of the elements of an array with a separator between them",
+            join,
+        );
+        registry.register(
+            "replace",
+            Arity::Exact(3),
+            "string with every occurrence of a part replaced",
+            replace,
+        );
+        registry.register(
+            "contains",
+            Arity::Exact(2),
+            "whether a string holds a part or an array an element",
+            contains,
+        );
+        registry.register(
+            "starts_with",
+            Arity::Exact(2),
+            "whether a string begins with a prefix",
+            starts_with,
+        );
+        registry.register(
+            "ends_with",
+            Arity::Exact(2),
+            "whether a string ends with a suffix",
+            ends_with,
+        );
+        registry.register(
+            "find",
+            Arity::Exact(2),
+            "position of a part in a string or -1, or the first array element a function holds true for or nil",
+            find,
+        );
+        registry.register(
+            "repeat",
+            Arity::Exact(2),
+            "string repeated a number of times",
+            repeat,
+        );
+        registry.register(
+            "chars",
+            Arity::Exact(1),
+            "array of the characters of a string",
+            chars,
+        );
+        registry.register(
+            "substr",
+            Arity::Between(2, 3),
+            "characters of a string from a position, to the end or for a length",
+            substr,
+        );
+        registry.register(
+            "pad_left",
+            Arity::Between(2, 3),
+            "string padded at the start to a width, with spaces by default",
+            pad_left,
+        );
+        registry.register(
+            "pad_right",
+            Arity::Between(2, 3),
+            "string padded at the end to a width, with spaces by default",
+            pad_right,
+        );
+        registry.register(
+            "reverse",
+            Arity::Exact(1),
+            "string or array in reverse order",
+            reverse,
+        );
+        registry.register(
+            "pop",
+            Arity::Exact(1),
+            "copy of an array without its last element",
+            pop,
+        );
+        registry.register(
+            "insert",
+            Arity::Exact(3),
+            "copy of an array with a value inserted at a position",
+            insert,
+        );
+        registry.register(
+            "remove",
+            Arity::Exact(2),
+            "copy of an array without the element at a position",
+            remove,
+        );
+        registry.register(
+            "slice",
+            Arity::Between(2, 3),
+            "elements of an array from a start up to an end, or to the last",
+            slice,
+        );
+        registry.register(
+            "concat",
+            Arity::AtLeast(1),
+            "array of the elements of every array given",
+            concat,
+        );
+        registry.register(
+            "sort",
+            Arity::Exact(1),
+            "array of strings or numbers in ascending order",
+            sort,
+        );
+        registry.register(
+            "sort_by",
+            Arity::Exact(2),
+            "array sorted by a comparator returning a negative, zero or positive integer",
+            sort_by,
+        );
+        registry.register(
+            "index_of",
+            Arity::Exact(2),
+            "position of the first element equal to a value, or -1",
+            index_of,
+        );
+        registry.register(
+            "unique",
+            Arity::Exact(1),
+            "array without repeated elements, keeping the first of each",
+            unique,
+        );
+        registry.register(
+            "zip",
+            Arity::Exact(2),
+            "array of tuples pairing the elements of two arrays",
+            zip,
+        );
+        registry.register(
+            "flatten",
+            Arity::Exact(1),
+            "array with nested arrays spliced in, one level deep",
+            flatten,
+        );
+        registry.register(
+            "map",
+            Arity::Exact(2),
+            "array of a function applied to every element",
+            map,
+        );
+        registry.register(
+            "filter",
+            Arity::Exact(2),
+            "array of the elements a function holds true for",
+            filter,
+        );
+        registry.register(
+            "reduce",
+            Arity::Between(2, 3),
+            "elements folded with a function, from an initial value or the first element",
+            reduce,
+        );
+        registry.register(
+            "any",
+            Arity::Exact(2),
+            "whether a function holds true for some element",
+            any,
+        );
+        registry.register(
+            "all",
+            Arity::Exact(2),
+            "whether a function holds true for every element",
+            all,
+        );
+        registry.register(
+            "type_of",
+            Arity::Exact(1),
+            "name of the type of a value",
+            type_of,
+        );
+        registry.register(
+            "int",
+            Arity::Exact(1),
+            "integer from a number, boolean or numeric string",
+            int,
+        );
+        registry.register(
+            "float",
+            Arity::Exact(1),
+            "float from a number, boolean or numeric string",
+            float,
+        );
+        registry.register(
+            "bool",
+            Arity::Exact(1),
+            "boolean from \"true\" or \"false\", or the truthiness of a value",
+            bool,
+        );
+        registry.register("repr", Arity::Exact(1), "source-like text of a value", repr);
+        registry.register(
+            "is_int",
+            Arity::Exact(1),
+            "whether a value is an integer",
+            is_int,
+        );
+        registry.register(
+            "is_float",
+            Arity::Exact(1),
+            "whether a value is a float",
+            is_float,
+        );
+        registry.register(
+            "is_number",
+            Arity::Exact(1),
+            "whether a value is an integer or a float",
+            is_number,
+        );
+        registry.register(
+            "is_string",
+            Arity::Exact(1),
+            "whether a value is a string",
+            is_string,
+        );
+        registry.register(
+            "is_bool",
+            Arity::Exact(1),
+            "whether a value is a boolean",
+            is_bool,
+        );
+        registry.register(
+            "is_array",
+            Arity::Exact(1),
+            "whether a value is an array",
+            is_array,
+        );
+        registry.register(
+            "is_hash",
+            Arity::Exact(1),
+            "whether a value is a hash",
+            is_hash,
+        );
+        registry.register(
+            "is_function",
+            Arity::Exact(1),
+            "whether a value is a function",
+            is_function,
+        );
+        registry.register("is_nil", Arity::Exact(1), "whether a value is nil", is_nil);
+        registry.define("PI", Objects::Float(consts::PI));
+        registry.define("E", Objects::Float(consts::E));
+        registry.register("abs", Arity::Exact(1), "absolute value of a number", abs);
+        registry.register(
+            "min",
+            Arity::AtLeast(1),
+            "smallest of the numbers given, or of an array",
+            min,
+        );
+        registry.register(
+            "max",
+            Arity::AtLeast(1),
+            "largest of the numbers given, or of an array",
+            max,
+        );
+        registry.register(
+            "clamp",
+            Arity::Exact(3),
+            "number limited to a lower and upper bound",
+            clamp,
+        );
+        registry.register(
+            "floor",
+            Arity::Exact(1),
+            "largest integer not above a number",
+            floor,
+        );
+        registry.register(
+            "ceil",
+            Arity::Exact(1),
+            "smallest integer not below a number",
+            ceil,
+        );
+        registry.register(
+            "round",
+            Arity::Exact(1),
+            "nearest integer to a number, halves away from zero",
+            round,
+        );
+        registry.register("sqrt", Arity::Exact(1), "square root of a number", sqrt);
+        registry.register(
+            "pow",
+            Arity::Exact(2),
+            "number raised to a power, an integer for integer powers",
+            pow,
+        );
+        registry.register("exp", Arity::Exact(1), "E raised to a number", exp);
+        registry.register(
+            "log",
+            Arity::Between(1, 2),
+            "natural logarithm of a number, or in the base given",
+            log,
+        );
+        registry.register("sin", Arity::Exact(1), "sine of an angle in radians", sin);
+        registry.register("cos", Arity::Exact(1), "cosine of an angle in radians", cos);
+        registry.register(
+            "tan",
+            Arity::Exact(1),
+            "tangent of an angle in radians",
+            tan,
+        );
+        registry.register("asin", Arity::Exact(1), "arc sine in radians", asin);
+        registry.register("acos", Arity::Exact(1), "arc cosine in radians", acos);
+        registry.register("atan", Arity::Exact(1), "arc tangent in radians", atan);
+        registry.register(
+            "atan2",
+            Arity::Exact(2),
+            "angle in radians of the point y, x",
+            atan2,
+        );
+        registry.register(
+            "gcd",
+            Arity::Exact(2),
+            "greatest common divisor of two integers",
+            gcd,
+        );
+        registry.register(
+            "lcm",
+            Arity::Exact(2),
+            "least common multiple of two integers",
+            lcm,
+        );
+        return registry;
+    }
+
+    /// Adds a builtin, replacing any registered under the same name.
+    pub fn register(&mut self, name: &str, arity: Arity, doc: &str, function: BuiltinFn) {
+        let builtin = BuiltinFunction {
+            name: name.to_string(),
+            arity,
+            doc: doc.to_string(),
+            function,
+        };
+        self.functions.insert(name.to_string(), builtin);
+    }
+
+    /// Adds a named value, replacing any defined under the same name.
+    pub fn define(&mut self, name: &str, value: Objects) {
+        self.constants.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BuiltinFunction> {
+        self.functions.get(name)
+    }
+
+    pub fn constant(&self, name: &str) -> Option<&Objects> {
+        self.constants.get(name)
+    }
+
+    /// Iterates over the builtins sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = &BuiltinFunction> {
+        self.functions.values()
+    }
+
+    /// Iterates over the named values sorted by name.
+    pub fn constants(&self) -> impl Iterator<Item = (&String, &Objects)> {
+        self.constants.iter()
+    }
+}
+
+fn type_error(message: &str) -> RuntimeError {
+    RuntimeError::new(message).with_kind("TypeError")
+}
+
+fn index_error(message: &str) -> RuntimeError {
+    RuntimeError::new(message).with_kind("IndexError")
+}
+
+fn value_error(message: &str) -> RuntimeError {
+    RuntimeError::new(message).with_kind("ValueError")
+}
+
+fn string_arg<'a>(name: &str, args: &'a [Objects], index: usize) -> Result<&'a str, RuntimeError> {
+    match &args[index] {
+        Objects::String(s) => return Ok(s),
+        s => {
+            let message = format!("{} expects a string but got {}", name, s.type_name());
+            return Err(type_error(&message));
+        }
+    }
+}
+
+fn arithmetic_error(message: &str) -> RuntimeError {
+    RuntimeError::new(message).with_kind("ArithmeticError")
+}
+
+/// A number argument, integers promoted to floats.
+fn number_arg(name: &str, args: &[Objects], index: usize) -> Result<f32, RuntimeError> {
+    match &args[index] {
+        Objects::Integer(s) => return Ok(*s as f32),
+        Objects::Float(s) => return Ok(*s),
+        s => {
+            let message = format!("{} expects a number but got {}", name, s.type_name());
+            return Err(type_error(&message));
+        }
+    }
+}
+
+/// Turns a rounded float back into an integer, failing when it doesn't fit.
+fn float_to_integer(name: &str, value: f32) -> Result<Objects, RuntimeError> {
+    if value.is_finite() && value >= i32::MIN as f32 && value < i32::MAX as f32 {
+        return Ok(Objects::Integer(value as i32));
+    }
+    let message = format!("{} of {} doesn't fit an integer", name, value);
+    return Err(arithmetic_error(&message));
+}
+
+fn array_arg<'a>(
+    name: &str,
+    args: &'a [Objects],
+    index: usize,
+) -> Result<&'a Vec<Objects>, RuntimeError> {
+    match &args[index] {
+        Objects::Array(s) => return Ok(s),
+        s => {
+            let message = format!("{} expects an array but got {}", name, s.type_name());
+            return Err(type_error(&message));
+        }
+    }
+}
+
+/// Checks that `index` is a position in an array of length `len`, or just
+/// past its end when `end` is set.
+fn position(index: i32, len: usize, end: bool) -> Result<usize, RuntimeError> {
+    let last = if end { len } else { len.saturating_sub(1) };
+    if index < 0 || index as usize > last || (len == 0 && end == false) {
+        let message = format!("index {} out of bounds for array of length {}", index, len);
+        return Err(index_error(&message));
+    }
+    return Ok(index as usize);
+}
+
+fn integer_arg(name: &str, args: &[Objects], index: usize) -> Result<i32, RuntimeError> {
+    match &args[index] {
+        Objects::Integer(s) => return Ok(*s),
+        s => {
+            let message = format!("{} expects an integer but got {}", name, s.type_name());
+            return Err(type_error(&message));
+        }
+    }
+}
+
+/// The text of a value as `str` shows it: strings without quotes, scalars
+/// without their type and other values the way `repr` writes them.
+pub fn to_text(value: &Objects) -> String {
+    match value {
+        Objects::String(s) => s.clone(),
+        Objects::Integer(s) => s.to_string(),
+        Objects::Float(s) => s.to_string(),
+        Objects::Boolean(s) => s.to_string(),
+        Objects::Error(s) => format!("{}: {}", s.kind, s.message),
+        s => source_text(s),
+    }
+}
+
+fn print(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::String(s) => println!("{}", s),
+        Objects::Integer(s) => println!("{}", s),
+        Objects::Float(s) => println!("{}", s),
+        Objects::Boolean(s) => println!("{}", s),
+        Objects::Array(s) => {
+            for i in s {
+                println!("{}", i);
+            }
+        }
+        Objects::Nil => println!("nil"),
+        s => println!("{}", s),
+    }
+    return Ok(Objects::Nil);
+}
+
+fn len(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::String(s) => return Ok(Objects::Integer(s.chars().count() as i32)),
+        Objects::Array(s) => return Ok(Objects::Integer(s.len() as i32)),
+        Objects::Tuple(s) => return Ok(Objects::Integer(s.len() as i32)),
+        Objects::Hash(s) => return Ok(Objects::Integer(s.len() as i32)),
+        _ => return Err(type_error("unsupported argument for len")),
+    }
+}
+
+fn first(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::String(s) => match s.chars().next() {
+            Some(c) => return Ok(Objects::String(c.to_string())),
+            None => return Err(index_error("first of an empty string")),
+        },
+        Objects::Array(s) => match s.first() {
+            Some(v) => return Ok(v.clone()),
+            None => return Err(index_error("first of an empty array")),
+        },
+        _ => return Err(type_error("unsupported argument for first")),
+    }
+}
+
+fn last(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::String(s) => match s.chars().last() {
+            Some(c) => return Ok(Objects::String(c.to_string())),
+            None => return Err(index_error("last of an empty string")),
+        },
+        Objects::Array(s) => match s.last() {
+            Some(v) => return Ok(v.clone()),
+            None => return Err(index_error("last of an empty array")),
+        },
+        _ => return Err(type_error("unsupported argument for last")),
+    }
+}
+
+fn push(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let mut f = array_arg("push", &args, 0)?.clone();
+    f.push(args[1].clone());
+    return Ok(Objects::Array(f));
+}
+
+fn compare(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let ordering = order(&args[0], &args[1])?;
+    return Ok(Objects::Integer(ordering as i32));
+}
+
+/// Orders two strings or two numbers, the way `compare` and `sort` do.
+fn order(left: &Objects, right: &Objects) -> Result<Ordering, RuntimeError> {
+    let (s, t) = match (left, right) {
+        (Objects::String(s), Objects::String(t)) => return Ok(s.cmp(t)),
+        (Objects::Integer(s), Objects::Integer(t)) => return Ok(s.cmp(t)),
+        (Objects::Float(s), Objects::Float(t)) => (*s, *t),
+        (Objects::Integer(s), Objects::Float(t)) => (*s as f32, *t),
+        (Objects::Float(s), Objects::Integer(t)) => (*s, *t as f32),
+        _ => {
+            return Err(type_error(
+                "compare function only supports two strings, integers or floats",
+            ))
+        }
+    };
+    match s.partial_cmp(&t) {
+        Some(o) => return Ok(o),
+        None => return Err(RuntimeError::new("compare function can't order NaN")),
+    }
+}
+
+fn upper(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::String(s) => return Ok(Objects::String(s.to_uppercase())),
+        _ => return Err(type_error("upper function only supports strings")),
+    }
+}
+
+fn lower(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::String(s) => return Ok(Objects::String(s.to_lowercase())),
+        _ => return Err(type_error("lower function only supports strings")),
+    }
+}
+
+fn keys(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Hash(s) => return Ok(Objects::Array(s.keys().cloned().collect())),
+        _ => return Err(type_error("keys function only supports hashes")),
+    }
+}
+
+fn values(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Hash(s) => return Ok(Objects::Array(s.values().cloned().collect())),
+        _ => return Err(type_error("values function only supports hashes")),
+    }
+}
+
+fn error(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let kind = match args.get(1) {
+        Some(Objects::String(s)) => s.clone(),
+        Some(_) => return Err(type_error("error function expects a string kind")),
+        None => "Error".to_string(),
+    };
+    match &args[0] {
+        Objects::String(s) => {
+            return Ok(Objects::Error(Rc::new(ErrorValue {
+                kind,
+                message: s.clone(),
+                line: None,
+                trace: Vec::new(),
+            })))
+        }
+        _ => return Err(type_error("error function expects a string message")),
+    }
+}
+
+fn ok(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::ok(args[0].clone()));
+}
+
+fn err(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::err(args[0].clone()));
+}
+
+fn unwrap(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Result(Ok(s)) => return Ok(*s.clone()),
+        Objects::Result(Err(s)) => {
+            let message = format!("called unwrap on an err result: {}", to_text(s));
+            return Err(RuntimeError::new(&message));
+        }
+        _ => return Err(type_error("unwrap function only supports results")),
+    }
+}
+
+fn unwrap_or(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Result(Ok(s)) => return Ok(*s.clone()),
+        Objects::Result(Err(_)) => return Ok(args[1].clone()),
+        _ => return Err(type_error("unwrap_or function only supports results")),
+    }
+}
+
+fn is_ok(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Result(s) => return Ok(Objects::Boolean(s.is_ok())),
+        _ => return Err(type_error("is_ok function only supports results")),
+    }
+}
+
+fn is_err(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Result(s) => return Ok(Objects::Boolean(s.is_err())),
+        _ => return Err(type_error("is_err function only supports results")),
+    }
+}
+
+fn str(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::String(to_text(&args[0])));
+}
+
+fn trim(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("trim", &args, 0)?;
+    return Ok(Objects::String(s.trim().to_string()));
+}
+
+fn split(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("split", &args, 0)?;
+    let parts: Vec<&str> = match args.get(1) {
+        None => s.split_whitespace().collect(),
+        Some(_) => match string_arg("split", &args, 1)? {
+            "" => s.split_terminator("").skip(1).collect(),
+            separator => s.split(separator).collect(),
+        },
+    };
+    let parts = parts.iter().map(|p| Objects::String(p.to_string()));
+    return Ok(Objects::Array(parts.collect()));
+}
+
+fn join(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let separator = match args.get(1) {
+        Some(_) => string_arg("join", &args, 1)?,
+        None => "",
+    };
+    match &args[0] {
+        Objects::Array(s) | Objects::Tuple(s) => {
+            let parts: Vec<String> = s.iter().map(to_text).collect();
+            return Ok(Objects::String(parts.join(separator)));
+        }
+        s => {
+            let message = format!("join expects an array but got {}", s.type_name());
+            return Err(type_error(&message));
+        }
+    }
+}
+
+fn replace(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("replace", &args, 0)?;
+    let from = string_arg("replace", &args, 1)?;
+    let to = string_arg("replace", &args, 2)?;
+    if from.is_empty() {
+        return Err(value_error("replace can't search for an empty string"));
+    }
+    return Ok(Objects::String(s.replace(from, to)));
+}
+
+fn contains(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    if let Objects::Array(s) = &args[0] {
+        return Ok(Objects::Boolean(s.contains(&args[1])));
+    }
+    let s = string_arg("contains", &args, 0)?;
+    let part = string_arg("contains", &args, 1)?;
+    return Ok(Objects::Boolean(s.contains(part)));
+}
+
+fn starts_with(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("starts_with", &args, 0)?;
+    let prefix = string_arg("starts_with", &args, 1)?;
+    return Ok(Objects::Boolean(s.starts_with(prefix)));
+}
+
+fn ends_with(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("ends_with", &args, 0)?;
+    let suffix = string_arg("ends_with", &args, 1)?;
+    return Ok(Objects::Boolean(s.ends_with(suffix)));
+}
+
+fn find(evaluator: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    if let Objects::Array(s) = &args[0] {
+        for element in s {
+            if evaluator
+                .call(args[1].clone(), vec![element.clone()])?
+                .is_truthy()
+            {
+                return Ok(element.clone());
+            }
+        }
+        return Ok(Objects::Nil);
+    }
+    let s = string_arg("find", &args, 0)?;
+    let part = string_arg("find", &args, 1)?;
+    // positions count characters, like indexing does
+    match s.find(part) {
+        Some(byte) => return Ok(Objects::Integer(s[..byte].chars().count() as i32)),
+        None => return Ok(Objects::Integer(-1)),
+    }
+}
+
+fn repeat(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("repeat", &args, 0)?;
+    let count = integer_arg("repeat", &args, 1)?;
+    if count < 0 {
+        return Err(value_error("repeat count can't be negative"));
+    }
+    return Ok(Objects::String(s.repeat(count as usize)));
+}
+
+fn chars(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("chars", &args, 0)?;
+    let chars = s.chars().map(|c| Objects::String(c.to_string()));
+    return Ok(Objects::Array(chars.collect()));
+}
+
+fn substr(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = string_arg("substr", &args, 0)?;
+    // widened so that huge positions and lengths can't overflow
+    let start = integer_arg("substr", &args, 1)? as i64;
+    let count = s.chars().count() as i64;
+    if start < 0 || start > count {
+        let message = format!(
+            "index {} out of bounds for string of length {}",
+            start, count
+        );
+        return Err(index_error(&message));
+    }
+    let length = match args.get(2) {
+        Some(_) => integer_arg("substr", &args, 2)? as i64,
+        None => count - start,
+    };
+    if length < 0 || start + length > count {
+        let message = format!(
+            "substring of length {} from {} out of bounds for string of length {}",
+            length, start, count
+        );
+        return Err(index_error(&message));
+    }
+    let part = s.chars().skip(start as usize).take(length as usize);
+    return Ok(Objects::String(part.collect()));
+}
+
+fn pad_left(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let (s, padding) = padding("pad_left", &args)?;
+    return Ok(Objects::String(padding + s));
+}
+
+fn pad_right(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let (s, padding) = padding("pad_right", &args)?;
+    return Ok(Objects::String(s.to_string() + &padding));
+}
+
+/// The string to pad and the fill needed to make it `width` characters long.
+fn padding<'a>(name: &str, args: &'a [Objects]) -> Result<(&'a str, String), RuntimeError> {
+    let s = string_arg(name, args, 0)?;
+    let width = integer_arg(name, args, 1)?;
+    let fill = match args.get(2) {
+        Some(_) => string_arg(name, args, 2)?,
+        None => " ",
+    };
+    if fill.chars().count() != 1 {
+        let message = format!("{} fill must be a single character", name);
+        return Err(value_error(&message));
+    }
+    let missing = (width as i64 - s.chars().count() as i64).max(0);
+    return Ok((s, fill.repeat(missing as usize)));
+}
+
+fn reverse(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    if let Objects::Array(s) = &args[0] {
+        return Ok(Objects::Array(s.iter().rev().cloned().collect()));
+    }
+    let s = string_arg("reverse", &args, 0)?;
+    return Ok(Objects::String(s.chars().rev().collect()));
+}
+
+fn pop(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let mut s = array_arg("pop", &args, 0)?.clone();
+    match s.pop() {
+        Some(_) => return Ok(Objects::Array(s)),
+        None => return Err(index_error("pop of an empty array")),
+    }
+}
+
+fn insert(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let mut s = array_arg("insert", &args, 0)?.clone();
+    let index = position(integer_arg("insert", &args, 1)?, s.len(), true)?;
+    s.insert(index, args[2].clone());
+    return Ok(Objects::Array(s));
+}
+
+fn remove(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let mut s = array_arg("remove", &args, 0)?.clone();
+    let index = position(integer_arg("remove", &args, 1)?, s.len(), false)?;
+    s.remove(index);
+    return Ok(Objects::Array(s));
+}
+
+fn slice(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("slice", &args, 0)?;
+    let start = position(integer_arg("slice", &args, 1)?, s.len(), true)?;
+    let end = match args.get(2) {
+        Some(_) => position(integer_arg("slice", &args, 2)?, s.len(), true)?,
+        None => s.len(),
+    };
+    if end < start {
+        let message = format!("slice end {} is before its start {}", end, start);
+        return Err(index_error(&message));
+    }
+    return Ok(Objects::Array(s[start..end].to_vec()));
+}
+
+fn concat(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let mut out = Vec::new();
+    for index in 0..args.len() {
+        out.extend(array_arg("concat", &args, index)?.iter().cloned());
+    }
+    return Ok(Objects::Array(out));
+}
+
+fn sort(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let mut s = array_arg("sort", &args, 0)?.clone();
+    let mut failed = None;
+    s.sort_by(|a, b| match order(a, b) {
+        Ok(o) => o,
+        Err(e) => {
+            failed.get_or_insert(e);
+            Ordering::Equal
+        }
+    });
+    match failed {
+        Some(e) => return Err(e),
+        None => return Ok(Objects::Array(s)),
+    }
+}
+
+fn sort_by(evaluator: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let mut s = array_arg("sort_by", &args, 0)?.clone();
+    let mut failed = None;
+    // the comparator returns a negative, zero or positive integer like compare
+    s.sort_by(|a, b| {
+        if failed.is_some() {
+            return Ordering::Equal;
+        }
+        match evaluator.call(args[1].clone(), vec![a.clone(), b.clone()]) {
+            Ok(Objects::Integer(n)) => n.cmp(&0),
+            Ok(v) => {
+                let message = format!("sort_by comparator returned {}", v.type_name());
+                failed = Some(type_error(&message));
+                Ordering::Equal
+            }
+            Err(e) => {
+                failed = Some(e);
+                Ordering::Equal
+            }
+        }
+    });
+    match failed {
+        Some(e) => return Err(e),
+        None => return Ok(Objects::Array(s)),
+    }
+}
+
+fn index_of(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("index_of", &args, 0)?;
+    match s.iter().position(|v| v == &args[1]) {
+        Some(i) => return Ok(Objects::Integer(i as i32)),
+        None => return Ok(Objects::Integer(-1)),
+    }
+}
+
+fn unique(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("unique", &args, 0)?;
+    let mut seen = HashSet::new();
+    let out = s.iter().filter(|v| seen.insert(*v)).cloned();
+    return Ok(Objects::Array(out.collect()));
+}
+
+fn zip(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("zip", &args, 0)?;
+    let t = array_arg("zip", &args, 1)?;
+    let pairs = s
+        .iter()
+        .zip(t)
+        .map(|(a, b)| Objects::Tuple(vec![a.clone(), b.clone()]));
+    return Ok(Objects::Array(pairs.collect()));
+}
+
+fn flatten(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("flatten", &args, 0)?;
+    let mut out = Vec::new();
+    for element in s {
+        match element {
+            Objects::Array(t) => out.extend(t.iter().cloned()),
+            t => out.push(t.clone()),
+        }
+    }
+    return Ok(Objects::Array(out));
+}
+
+fn map(evaluator: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("map", &args, 0)?;
+    let mut out = Vec::new();
+    for element in s {
+        out.push(evaluator.call(args[1].clone(), vec![element.clone()])?);
+    }
+    return Ok(Objects::Array(out));
+}
+
+fn filter(evaluator: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("filter", &args, 0)?;
+    let mut out = Vec::new();
+    for element in s {
+        if evaluator
+            .call(args[1].clone(), vec![element.clone()])?
+            .is_truthy()
+        {
+            out.push(element.clone());
+        }
+    }
+    return Ok(Objects::Array(out));
+}
+
+fn reduce(evaluator: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("reduce", &args, 0)?;
+    // without an initial value the first element starts the fold
+    let (mut acc, rest) = match (args.get(2), s.split_first()) {
+        (Some(initial), _) => (initial.clone(), &s[..]),
+        (None, Some((first, rest))) => (first.clone(), rest),
+        (None, None) => {
+            return Err(value_error(
+                "reduce of an empty array without an initial value",
+            ))
+        }
+    };
+    for element in rest {
+        acc = evaluator.call(args[1].clone(), vec![acc, element.clone()])?;
+    }
+    return Ok(acc);
+}
+
+fn any(evaluator: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("any", &args, 0)?;
+    for element in s {
+        if evaluator
+            .call(args[1].clone(), vec![element.clone()])?
+            .is_truthy()
+        {
+            return Ok(Objects::Boolean(true));
+        }
+    }
+    return Ok(Objects::Boolean(false));
+}
+
+fn all(evaluator: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let s = array_arg("all", &args, 0)?;
+    for element in s {
+        if evaluator
+            .call(args[1].clone(), vec![element.clone()])?
+            .is_truthy()
+            == false
+        {
+            return Ok(Objects::Boolean(false));
+        }
+    }
+    return Ok(Objects::Boolean(true));
+}
+
+fn abs(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Integer(s) => match s.checked_abs() {
+            Some(v) => return Ok(Objects::Integer(v)),
+            None => return Err(arithmetic_error("integer overflow")),
+        },
+        _ => return Ok(Objects::Float(number_arg("abs", &args, 0)?.abs())),
+    }
+}
+
+/// The smallest or largest of the arguments, or of the elements of a single
+/// array argument, keeping its type.
+fn extreme(name: &str, args: &[Objects], wanted: Ordering) -> Result<Objects, RuntimeError> {
+    let values = match args {
+        [Objects::Array(s)] => &s[..],
+        s => s,
+    };
+    let mut best = match values.first() {
+        Some(s) => s,
+        None => {
+            let message = format!("{} of an empty array", name);
+            return Err(value_error(&message));
+        }
+    };
+    for index in 0..values.len() {
+        number_arg(name, values, index)?;
+        if order(&values[index], best)? == wanted {
+            best = &values[index];
+        }
+    }
+    return Ok(best.clone());
+}
+
+fn min(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return extreme("min", &args, Ordering::Less);
+}
+
+fn max(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return extreme("max", &args, Ordering::Greater);
+}
+
+fn clamp(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    for index in 0..3 {
+        number_arg("clamp", &args, index)?;
+    }
+    if order(&args[1], &args[2])? == Ordering::Greater {
+        return Err(value_error("clamp lower bound is above the upper bound"));
+    }
+    if order(&args[0], &args[1])? == Ordering::Less {
+        return Ok(args[1].clone());
+    }
+    if order(&args[0], &args[2])? == Ordering::Greater {
+        return Ok(args[2].clone());
+    }
+    return Ok(args[0].clone());
+}
+
+fn floor(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    if let Objects::Integer(s) = args[0] {
+        return Ok(Objects::Integer(s));
+    }
+    return float_to_integer("floor", number_arg("floor", &args, 0)?.floor());
+}
+
+fn ceil(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    if let Objects::Integer(s) = args[0] {
+        return Ok(Objects::Integer(s));
+    }
+    return float_to_integer("ceil", number_arg("ceil", &args, 0)?.ceil());
+}
+
+fn round(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    if let Objects::Integer(s) = args[0] {
+        return Ok(Objects::Integer(s));
+    }
+    return float_to_integer("round", number_arg("round", &args, 0)?.round());
+}
+
+fn sqrt(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let x = number_arg("sqrt", &args, 0)?;
+    if x < 0.0 {
+        return Err(arithmetic_error("sqrt of a negative number"));
+    }
+    return Ok(Objects::Float(x.sqrt()));
+}
+
+fn pow(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    // integers raised to a non-negative integer power stay integers
+    if let (Objects::Integer(base), Objects::Integer(exponent)) = (&args[0], &args[1]) {
+        if *exponent >= 0 {
+            match base.checked_pow(*exponent as u32) {
+                Some(v) => return Ok(Objects::Integer(v)),
+                None => return Err(arithmetic_error("integer overflow")),
+            }
+        }
+    }
+    let base = number_arg("pow", &args, 0)?;
+    let exponent = number_arg("pow", &args, 1)?;
+    if base == 0.0 && exponent < 0.0 {
+        return Err(arithmetic_error("division by zero"));
+    }
+    if base < 0.0 && exponent.fract() != 0.0 {
+        return Err(arithmetic_error("fractional power of a negative number"));
+    }
+    return Ok(Objects::Float(base.powf(exponent)));
+}
+
+fn exp(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Float(number_arg("exp", &args, 0)?.exp()));
+}
+
+fn log(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let x = number_arg("log", &args, 0)?;
+    if x <= 0.0 {
+        return Err(arithmetic_error("log of a number that isn't positive"));
+    }
+    match args.get(1) {
+        Some(_) => {
+            let base = number_arg("log", &args, 1)?;
+            if base <= 0.0 || base == 1.0 {
+                return Err(arithmetic_error("log base must be positive and not 1"));
+            }
+            return Ok(Objects::Float(x.log(base)));
+        }
+        None => return Ok(Objects::Float(x.ln())),
+    }
+}
+
+fn sin(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Float(number_arg("sin", &args, 0)?.sin()));
+}
+
+fn cos(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Float(number_arg("cos", &args, 0)?.cos()));
+}
+
+fn tan(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Float(number_arg("tan", &args, 0)?.tan()));
+}
+
+fn asin(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let x = number_arg("asin", &args, 0)?;
+    if (-1.0..=1.0).contains(&x) == false {
+        return Err(arithmetic_error("asin of a number outside -1 to 1"));
+    }
+    return Ok(Objects::Float(x.asin()));
+}
+
+fn acos(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let x = number_arg("acos", &args, 0)?;
+    if (-1.0..=1.0).contains(&x) == false {
+        return Err(arithmetic_error("acos of a number outside -1 to 1"));
+    }
+    return Ok(Objects::Float(x.acos()));
+}
+
+fn atan(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Float(number_arg("atan", &args, 0)?.atan()));
+}
+
+fn atan2(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let y = number_arg("atan2", &args, 0)?;
+    let x = number_arg("atan2", &args, 1)?;
+    return Ok(Objects::Float(y.atan2(x)));
+}
+
+fn gcd(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let a = integer_arg("gcd", &args, 0)?;
+    let b = integer_arg("gcd", &args, 1)?;
+    match i32::try_from(greatest_divisor(a, b)) {
+        Ok(v) => return Ok(Objects::Integer(v)),
+        Err(_) => return Err(arithmetic_error("integer overflow")),
+    }
+}
+
+fn lcm(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let a = integer_arg("lcm", &args, 0)?;
+    let b = integer_arg("lcm", &args, 1)?;
+    if a == 0 || b == 0 {
+        return Ok(Objects::Integer(0));
+    }
+    let multiple = (a as i64 / greatest_divisor(a, b) as i64 * b as i64).abs();
+    match i32::try_from(multiple) {
+        Ok(v) => return Ok(Objects::Integer(v)),
+        Err(_) => return Err(arithmetic_error("integer overflow")),
+    }
+}
+
+fn greatest_divisor(a: i32, b: i32) -> u32 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    return a;
+}
+
+fn type_of(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::String(args[0].type_name().to_string()));
+}
+
+fn int(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Integer(s) => return Ok(Objects::Integer(*s)),
+        Objects::Float(s) => return float_to_integer("int", s.trunc()),
+        Objects::Boolean(s) => return Ok(Objects::Integer(*s as i32)),
+        Objects::String(s) => match s.trim().parse::<i32>() {
+            Ok(v) => return Ok(Objects::Integer(v)),
+            Err(_) => {
+                let message = format!("can't parse {:?} as an integer", s);
+                return Err(value_error(&message));
+            }
+        },
+        s => {
+            let message = format!("can't convert {} to an integer", s.type_name());
+            return Err(type_error(&message));
+        }
+    }
+}
+
+fn float(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::Integer(s) => return Ok(Objects::Float(*s as f32)),
+        Objects::Float(s) => return Ok(Objects::Float(*s)),
+        Objects::Boolean(s) => return Ok(Objects::Float(*s as i32 as f32)),
+        Objects::String(s) => match s.trim().parse::<f32>() {
+            Ok(v) => return Ok(Objects::Float(v)),
+            Err(_) => {
+                let message = format!("can't parse {:?} as a float", s);
+                return Err(value_error(&message));
+            }
+        },
+        s => {
+            let message = format!("can't convert {} to a float", s.type_name());
+            return Err(type_error(&message));
+        }
+    }
+}
+
+fn bool(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    match &args[0] {
+        Objects::String(s) => match s.trim() {
+            "true" => return Ok(Objects::Boolean(true)),
+            "false" => return Ok(Objects::Boolean(false)),
+            _ => {
+                let message = format!("can't parse {:?} as a boolean", s);
+                return Err(value_error(&message));
+            }
+        },
+        s => return Ok(Objects::Boolean(s.is_truthy())),
+    }
+}
+
+fn repr(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::String(source_text(&args[0])));
+}
+
+/// How a value would be written in a program; values without a literal
+/// form are shown between angle brackets.
+fn source_text(value: &Objects) -> String {
+    return write_source(value, &mut Vec::new());
+}
+
+/// Builds the source text of a value, showing a struct met again inside
+/// itself as `<cycle>`.
+fn write_source(value: &Objects, seen: &mut SeenStructs) -> String {
+    let list = |values: &[Objects], seen: &mut SeenStructs| {
+        let parts: Vec<String> = values.iter().map(|v| write_source(v, seen)).collect();
+        parts.join(", ")
+    };
+    match value {
+        Objects::Integer(s) => s.to_string(),
+        // whole floats keep their point so they read back as floats
+        Objects::Float(s) if s.is_finite() && s.fract() == 0.0 => format!("{:.1}", s),
+        Objects::Float(s) => s.to_string(),
+        Objects::String(s) => format!("{:?}", s),
+        Objects::Boolean(s) => s.to_string(),
+        Objects::Nil => "nil".to_string(),
+        Objects::Array(s) => format!("[{}]", list(s, seen)),
+        Objects::Tuple(s) if s.len() == 1 => format!("({},)", list(s, seen)),
+        Objects::Tuple(s) => format!("({})", list(s, seen)),
+        Objects::Hash(s) => {
+            let mut entries: Vec<String> = s
+                .iter()
+                .map(|(k, v)| format!("{}: {}", write_source(k, seen), write_source(v, seen)))
+                .collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Objects::Variant(s) if s.values.is_empty() => s.kind.name.clone(),
+        Objects::Variant(s) => format!("{}({})", s.kind.name, list(&s.values, seen)),
+        Objects::Struct(s) if seen.contains(&Rc::as_ptr(s)) => "<cycle>".to_string(),
+        Objects::Struct(s) => {
+            seen.push(Rc::as_ptr(s));
+            let s = s.borrow();
+            let fields: Vec<String> = s
+                .kind
+                .fields
+                .iter()
+                .zip(&s.values)
+                .map(|(name, value)| format!("{}: {}", name, write_source(value, seen)))
+                .collect();
+            seen.pop();
+            format!("{} {{ {} }}", s.kind.name, fields.join(", "))
+        }
+        Objects::Result(Ok(s)) => format!("ok({})", write_source(s, seen)),
+        Objects::Result(Err(s)) => format!("err({})", write_source(s, seen)),
+        Objects::Error(s) => format!("error({:?}, {:?})", s.message, s.kind),
+        Objects::Function(s) => format!("<fn {}>", s.name),
+        Objects::BuiltIn(s) => format!("<builtin {}>", s.name),
+        Objects::Constructor(s) => format!("<constructor {}::{}>", s.enum_name, s.name),
+        Objects::Class(s) => format!("<class {}>", s.name),
+        Objects::StructType(s) => format!("<struct {}>", s.name),
+        Objects::Instance(s) => format!("<{} instance>", s.borrow().class.name),
+        Objects::Module(s) => format!("<module {}>", s.name),
+    }
+}
+
+fn is_int(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(args[0], Objects::Integer(_))));
+}
+
+fn is_float(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(args[0], Objects::Float(_))));
+}
+
+fn is_number(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    let number = matches!(args[0], Objects::Integer(_) | Objects::Float(_));
+    return Ok(Objects::Boolean(number));
+}
+
+fn is_string(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(args[0], Objects::String(_))));
+}
+
+fn is_bool(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(args[0], Objects::Boolean(_))));
+}
+
+fn is_array(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(args[0], Objects::Array(_))));
+}
+
+fn is_hash(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(args[0], Objects::Hash(_))));
+}
+
+fn is_function(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(
+        args[0],
+        Objects::Function(_) | Objects::BuiltIn(_)
+    )));
+}
+
+fn is_nil(_: &mut Evaluator, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+    return Ok(Objects::Boolean(matches!(args[0], Objects::Nil)));
+}
@@ -1,41 +1,198 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
-use object::{Environment, Function, Objects};
+use object::{Environment, Function, Objects, RuntimeError};
 
+use crate::builtins::{Arity, BuiltinFn, BuiltinRegistry};
 use crate::parser;
-use crate::{object, tokens};
+use crate::{lexer, object, tokens};
+
+/// The standard library, written in Axel and built into the binary. Each
+/// module can be imported as `std/<name>` and the prelude loads them all
+/// into the `std` namespace.
+const STD_MODULES: [(&str, &str); 3] = [
+    ("collections", include_str!("prelude/collections.axel")),
+    ("functional", include_str!("prelude/functional.axel")),
+    ("strings", include_str!("prelude/strings.axel")),
+];
+
+/// Call arguments passed by name, in call order.
+type NamedArguments = Vec<(String, Objects)>;
 
 pub struct Evaluator {
-    environment: Environment,
+    environment: Rc<RefCell<Environment>>,
+    /// The file being evaluated, imports are resolved relative to it.
+    current_file: Option<PathBuf>,
+    /// Directories searched for imports not found next to the importing file.
+    search_paths: Vec<PathBuf>,
+    /// Modules already evaluated, by canonical path.
+    modules: HashMap<PathBuf, Objects>,
+    /// Modules being evaluated, outermost first, to detect import cycles.
+    loading: Vec<PathBuf>,
+    /// Names exported by the module being evaluated.
+    exports: Vec<String>,
+    /// Native functions callable from every scope.
+    builtins: BuiltinRegistry,
 }
 
 impl Evaluator {
     pub fn new() -> Self {
         Evaluator {
-            environment: Environment::new(),
+            environment: Rc::new(RefCell::new(Environment::new())),
+            current_file: None,
+            search_paths: Vec::new(),
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            exports: Vec::new(),
+            builtins: BuiltinRegistry::new(),
+        }
+    }
+
+    pub fn builtins(&self) -> &BuiltinRegistry {
+        &self.builtins
+    }
+
+    /// Makes a native function callable from programs run by this evaluator.
+    #[allow(dead_code)]
+    pub fn register_builtin(&mut self, name: &str, arity: Arity, doc: &str, function: BuiltinFn) {
+        self.builtins.register(name, arity, doc, function);
+    }
+
+    /// Calls a function value with positional arguments, so builtins can take
+    /// callbacks.
+    pub fn call(&mut self, callee: Objects, args: Vec<Objects>) -> Result<Objects, RuntimeError> {
+        self.call_object(callee, args, Vec::new(), None)
+    }
+
+    /// Sets the file the program was read from.
+    pub fn set_file(&mut self, path: PathBuf) {
+        if let Ok(s) = path.canonicalize() {
+            self.loading = vec![s];
         }
+        self.current_file = Some(path);
     }
 
-    pub fn eval_statement<'a>(
+    pub fn add_search_path(&mut self, path: PathBuf) {
+        self.search_paths.push(path);
+    }
+
+    /// Binds `std` to a module holding every standard library module.
+    pub fn load_prelude(&mut self) -> Result<(), RuntimeError> {
+        let mut members = HashMap::new();
+        for (name, _source) in STD_MODULES {
+            let module = self.import_module(&format!("std/{}", name))?;
+            members.insert(name.to_string(), module);
+        }
+        let prelude = Objects::Module(Rc::new(object::Module {
+            name: "std".to_string(),
+            members,
+        }));
+        return self
+            .environment
+            .borrow_mut()
+            .declare("std".to_string(), prelude, false);
+    }
+
+    /// Evaluates a whole program, stopping at the first error or top level
+    /// `return` or `?`, and yields the value of the last statement evaluated.
+    pub fn eval_program(
+        &mut self,
+        statements: Vec<parser::Statement>,
+    ) -> Result<object::Objects, RuntimeError> {
+        match self.eval_block(&statements) {
+            Ok(s) => return Ok(s),
+            Err(RuntimeError {
+                returned: Some(s), ..
+            }) => return Ok(*s),
+            Err(e) => return Err(e),
+        }
+    }
+
+    pub fn eval_statement(
         &mut self,
         statement: parser::Statement,
-    ) -> Result<object::Objects, &'a str> {
+    ) -> Result<object::Objects, RuntimeError> {
         match statement {
             parser::Statement::VarStatement { name, value } => {
                 let e = *value.clone();
                 let eval = self.eval_expression(e)?;
-                self.environment.add(name, eval.clone());
+                self.environment
+                    .borrow_mut()
+                    .declare(name, eval.clone(), true)?;
                 return Ok(eval);
             }
             parser::Statement::ConstStatement { name, value } => {
                 let e = *value.clone();
                 let eval = self.eval_expression(e)?;
-                self.environment.add(name, eval.clone());
+                self.environment
+                    .borrow_mut()
+                    .declare(name, eval.clone(), false)?;
+                return Ok(eval);
+            }
+            parser::Statement::DestructureStatement {
+                pattern,
+                value,
+                mutable,
+            } => {
+                let eval = self.eval_expression(*value)?;
+                let mut bindings: Vec<(String, Objects)> = Vec::new();
+                if self.match_pattern(&pattern, &eval, &mut bindings)? == false {
+                    return Err(RuntimeError::new(&format!(
+                        "cannot destructure {} with this pattern",
+                        eval.type_name()
+                    )));
+                }
+                for (name, bound) in bindings {
+                    self.environment
+                        .borrow_mut()
+                        .declare(name, bound, mutable)?;
+                }
                 return Ok(eval);
             }
+            parser::Statement::ImportStatement { path, alias, line } => {
+                let module = match self.import_module(&path) {
+                    Ok(s) => s,
+                    Err(e) => return Err(e.with_line(line)),
+                };
+                self.environment
+                    .borrow_mut()
+                    .declare(alias, module.clone(), false)?;
+                return Ok(module);
+            }
+            parser::Statement::ExportStatement { statement } => {
+                let names = match &*statement {
+                    parser::Statement::VarStatement { name, value: _ }
+                    | parser::Statement::ConstStatement { name, value: _ } => vec![name.clone()],
+                    parser::Statement::ExpressionStatement { value } => match &**value {
+                        parser::Expression::FunctionExpr { identifier, .. }
+                        | parser::Expression::ClassExpr { identifier, .. }
+                        | parser::Expression::StructExpr { identifier, .. } => {
+                            vec![identifier.clone()]
+                        }
+                        // an enum declares its variants, not its own name
+                        parser::Expression::EnumExpr { variants, .. } => {
+                            variants.iter().map(|(name, _)| name.clone()).collect()
+                        }
+                        _ => return Err(RuntimeError::new("only declarations can be exported")),
+                    },
+                    _ => return Err(RuntimeError::new("only declarations can be exported")),
+                };
+                let eval = self.eval_statement(*statement)?;
+                self.exports.extend(names);
+                return Ok(eval);
+            }
+            parser::Statement::ThrowStatement { value, line } => {
+                let eval = self.eval_expression(*value)?;
+                return Err(RuntimeError::thrown(eval).with_line(line));
+            }
             parser::Statement::ReturnStatement { value } => {
                 let e = *value.clone();
-                return self.eval_expression(e);
+                let eval = self.eval_expression(e)?;
+                return Err(RuntimeError::early_return(eval));
             }
             parser::Statement::ExpressionStatement { value } => {
                 let e = *value.clone();
@@ -44,10 +201,10 @@ impl Evaluator {
         }
     }
 
-    pub fn eval_expression<'a>(
+    pub fn eval_expression(
         &mut self,
         expression: parser::Expression,
-    ) -> Result<object::Objects, &'a str> {
+    ) -> Result<object::Objects, RuntimeError> {
         match expression {
             parser::Expression::NumberLit { number } => {
                 return Ok(object::Objects::Integer(number));
@@ -59,22 +216,35 @@ impl Evaluator {
                 return Ok(object::Objects::String(string));
             }
             parser::Expression::BoolExp { value } => return Ok(object::Objects::Boolean(value)),
-            parser::Expression::IdentifierLit { name } => {
-                let val = self.environment.search(name);
+            parser::Expression::NilLit => return Ok(object::Objects::Nil),
+            parser::Expression::IdentifierLit { name, line } => {
+                let val = self.environment.borrow().search(name.clone());
                 match val {
-                    Some(s) => return Ok(s.clone()),
-                    None => return Err("identifier not found"),
+                    Some(s) => return Ok(s),
+                    None => {
+                        if let Some(s) = self.builtins.constant(&name) {
+                            return Ok(s.clone());
+                        }
+                        match self.builtins.get(&name) {
+                            Some(s) => return Ok(Objects::BuiltIn(s.clone())),
+                            None => return Err(not_found(&name).with_line(line)),
+                        }
+                    }
                 }
             }
 
-            parser::Expression::Prefix { operator, right } => {
+            parser::Expression::Prefix {
+                operator,
+                right,
+                line,
+            } => {
                 let evaluate_right = self.eval_expression(*right);
                 match evaluate_right {
                     Ok(s) => {
                         let right_eval = self.eval_prefix_expression(operator, s);
                         match right_eval {
                             Ok(s) => return Ok(s),
-                            Err(e) => return Err(e),
+                            Err(e) => return Err(e.with_line(line)),
                         }
                     }
                     Err(e) => return Err(e),
@@ -85,13 +255,29 @@ impl Evaluator {
                 left,
                 right,
                 operator,
+                line,
             } => {
                 let evaluate_left = self.eval_expression(*left)?;
+                // && and || short-circuit, so the right side is only evaluated
+                // when the left side doesn't already decide the result
+                match operator {
+                    tokens::TokenTypes::And if evaluate_left.is_truthy() == false => {
+                        return Ok(Objects::Boolean(false));
+                    }
+                    tokens::TokenTypes::Or if evaluate_left.is_truthy() => {
+                        return Ok(Objects::Boolean(true));
+                    }
+                    tokens::TokenTypes::And | tokens::TokenTypes::Or => {
+                        let evaluate_right = self.eval_expression(*right)?;
+                        return Ok(Objects::Boolean(evaluate_right.is_truthy()));
+                    }
+                    _ => {}
+                }
                 let evaluate_right = self.eval_expression(*right)?;
                 let infix = self.eval_infix_expression(operator, evaluate_left, evaluate_right);
                 match infix {
                     Ok(s) => return Ok(s),
-                    Err(e) => return Err(e),
+                    Err(e) => return Err(e.with_line(line)),
                 }
             }
 
@@ -100,86 +286,358 @@ impl Evaluator {
                 then,
                 other,
             } => {
-                return Ok(self.evaluate_if_condition(*condition, *then, other)?);
+                return self.evaluate_if_condition(*condition, *then, other);
             }
             parser::Expression::FunctionExpr {
                 identifier,
                 parameters,
                 body,
             } => {
-                let function = Function::new(parameters, body);
-                self.environment
-                    .add(identifier, Objects::Function(function.clone()));
+                let function = Function::new(
+                    identifier.clone(),
+                    parameters,
+                    *body,
+                    self.environment.clone(),
+                );
+                self.environment.borrow_mut().declare(
+                    identifier,
+                    Objects::Function(function.clone()),
+                    false,
+                )?;
                 return Ok(Objects::Function(function));
             }
 
             parser::Expression::CallExpr {
                 identifier,
                 parameters,
+                line,
             } => {
-                let val = self.environment.search(identifier.clone());
+                let val = self.environment.borrow().search(identifier.clone());
                 let call_identifier;
                 match val {
-                    Some(s) => call_identifier = s.clone(),
-                    None => {
-                        let builtin = object::BuiltinFunction::new(identifier);
-                        call_identifier = Objects::BuiltIn(builtin);
-                    }
+                    Some(s) => call_identifier = s,
+                    None => match self.builtins.get(&identifier) {
+                        Some(s) => call_identifier = Objects::BuiltIn(s.clone()),
+                        None => return Err(not_found(&identifier).with_line(line)),
+                    },
                 };
-                let param_values = self.eval_call_params(parameters)?;
-
-                let mut func_names: Vec<String> = Vec::new();
-                //let mut inner_environment;
-                let mut evaluated: Option<Result<Objects, &'a str>> = None;
-                match call_identifier {
-                    Objects::Function(func) => {
-                        for i in func.parameters {
-                            func_names.push(i);
+                let (param_values, named_values) = self.eval_call_params(parameters)?;
+                return self.call_object(call_identifier, param_values, named_values, Some(line));
+            }
+            parser::Expression::ClassExpr {
+                identifier,
+                superclass,
+                fields,
+                initializers,
+                methods,
+            } => {
+                let mut parent: Option<Rc<object::Class>> = None;
+                if let Some(name) = superclass {
+                    match self.environment.borrow().search(name.clone()) {
+                        Some(Objects::Class(s)) => parent = Some(s),
+                        _ => return Err(RuntimeError::new(&format!("{} is not a class", name))),
+                    }
+                }
+                // field defaults are evaluated once, when the class is declared
+                let mut class_fields: Vec<(String, Objects)> = Vec::new();
+                for (name, initializer) in fields.into_iter().zip(initializers) {
+                    class_fields.push((name, self.eval_expression(initializer)?));
+                }
+                let mut class_methods: HashMap<String, Function> = HashMap::new();
+                for method in methods {
+                    if let parser::Expression::FunctionExpr {
+                        identifier,
+                        parameters,
+                        body,
+                    } = method
+                    {
+                        let function = Function::new(
+                            identifier.clone(),
+                            parameters,
+                            *body,
+                            self.environment.clone(),
+                        );
+                        class_methods.insert(identifier, function);
+                    }
+                }
+                let class = Objects::Class(Rc::new(object::Class {
+                    name: identifier.clone(),
+                    superclass: parent,
+                    fields: class_fields,
+                    methods: class_methods,
+                }));
+                self.environment
+                    .borrow_mut()
+                    .declare(identifier, class.clone(), false)?;
+                return Ok(class);
+            }
+            parser::Expression::MemberExpr {
+                object,
+                member,
+                line,
+            } => {
+                let obj = self.eval_expression(*object)?;
+                match self.eval_member(obj, &member) {
+                    Ok(s) => return Ok(s),
+                    Err(e) => return Err(e.with_line(line)),
+                }
+            }
+            parser::Expression::MethodCall {
+                object,
+                method,
+                parameters,
+                line,
+            } => {
+                let obj = self.eval_expression(*object)?;
+                let (mut param_values, named_values) = self.eval_call_params(parameters)?;
+                let called = match obj {
+                    Objects::Instance(_) | Objects::Module(_) => {
+                        match self.eval_member(obj, &method) {
+                            Ok(s) => self.call_object(s, param_values, named_values, Some(line)),
+                            Err(e) => Err(e.with_line(line)),
                         }
-                        //inner_environment = func.environment;
-                        for i in 0..func_names.len() {
-                            self.environment
-                                .add(func_names[i].clone(), param_values[i].clone());
+                    }
+                    // `value.name(args)` on a built-in value is `name(value, args)`
+                    _ if self.builtins.get(&method).is_some() => {
+                        param_values.insert(0, obj);
+                        let builtin = Objects::BuiltIn(self.builtins.get(&method).unwrap().clone());
+                        self.call_object(builtin, param_values, named_values, Some(line))
+                    }
+                    _ => {
+                        let message = format!("{} has no method {}", obj.type_name(), method);
+                        Err(RuntimeError::new(&message).with_line(line))
+                    }
+                };
+                return called;
+            }
+            parser::Expression::EnumExpr {
+                identifier,
+                variants,
+            } => {
+                // variants without payloads are values, the others are
+                // constructor functions
+                for (name, fields) in variants {
+                    let kind = Rc::new(object::VariantType {
+                        enum_name: identifier.clone(),
+                        name: name.clone(),
+                        fields,
+                    });
+                    let value;
+                    if kind.fields.is_empty() {
+                        value = Objects::Variant(object::Variant {
+                            kind,
+                            values: Vec::new(),
+                        });
+                    } else {
+                        value = Objects::Constructor(kind);
+                    }
+                    self.environment.borrow_mut().declare(name, value, false)?;
+                }
+                return Ok(Objects::Nil);
+            }
+            parser::Expression::StructExpr { identifier, fields } => {
+                let kind = Objects::StructType(Rc::new(object::StructType {
+                    name: identifier.clone(),
+                    fields,
+                }));
+                self.environment
+                    .borrow_mut()
+                    .declare(identifier, kind.clone(), false)?;
+                return Ok(kind);
+            }
+            parser::Expression::StructLit {
+                identifier,
+                fields,
+                values,
+                line,
+            } => {
+                let kind;
+                match self.environment.borrow().search(identifier.clone()) {
+                    Some(Objects::StructType(s)) => kind = s,
+                    _ => {
+                        let message = format!("{} is not a struct", identifier);
+                        return Err(RuntimeError::new(&message).with_line(line));
+                    }
+                }
+                let mut field_values: Vec<Option<Objects>> = vec![None; kind.fields.len()];
+                for (name, value) in fields.into_iter().zip(values) {
+                    match kind.fields.iter().position(|f| *f == name) {
+                        Some(i) => field_values[i] = Some(self.eval_expression(value)?),
+                        None => {
+                            let message = format!("{} has no field {}", kind.name, name);
+                            return Err(RuntimeError::new(&message).with_line(line));
                         }
-
-                        for statements in *func.body {
-                            evaluated = Some(self.eval_statement(statements));
+                    }
+                }
+                let mut struct_values: Vec<Objects> = Vec::new();
+                for (name, value) in kind.fields.iter().zip(field_values) {
+                    match value {
+                        Some(s) => struct_values.push(s),
+                        None => {
+                            let message = format!("missing field {} for {}", name, kind.name);
+                            return Err(RuntimeError::new(&message).with_line(line));
                         }
-
-                        for i in 0..func_names.len() {
-                            self.environment.remove(func_names[i].clone());
+                    }
+                }
+                return Ok(Objects::Struct(Rc::new(RefCell::new(object::Struct {
+                    kind,
+                    values: struct_values,
+                }))));
+            }
+            parser::Expression::SuperCall {
+                method,
+                parameters,
+                line,
+            } => {
+                // methods of a subclass are bound with `super` naming the
+                // superclass of the class that defines them
+                let superclass = self.environment.borrow().search("super".to_string());
+                let instance = self.environment.borrow().search("self".to_string());
+                let (param_values, named_values) = self.eval_call_params(parameters)?;
+                match (superclass, instance) {
+                    (Some(Objects::Class(class)), Some(Objects::Instance(s))) => {
+                        match class.find_method(&method) {
+                            Some((owner, found)) => {
+                                let bound = self.bind_method(&owner, &found, s);
+                                return self.call_object(
+                                    bound,
+                                    param_values,
+                                    named_values,
+                                    Some(line),
+                                );
+                            }
+                            None => {
+                                let message = format!("{} has no method {}", class.name, method);
+                                return Err(RuntimeError::new(&message).with_line(line));
+                            }
                         }
                     }
-                    Objects::BuiltIn(mut func) => {
-                        evaluated = Some(func.call(param_values));
+                    _ => {
+                        return Err(RuntimeError::new(
+                            "super can only be used in methods of a class with a superclass",
+                        )
+                        .with_line(line))
                     }
-                    _ => return Err("object isn't a function"),
                 }
-
-                match evaluated {
-                    Some(s) => match s {
-                        Ok(t) => return Ok(t),
-                        Err(e) => return Err(e),
-                    },
-                    None => return Err("evaluating function"),
+            }
+            parser::Expression::MemberChange {
+                object,
+                member,
+                right,
+                line,
+            } => {
+                let obj = self.eval_expression(*object)?;
+                let right_obj = self.eval_expression(*right)?;
+                match obj {
+                    Objects::Instance(s) => {
+                        let mut instance = s.borrow_mut();
+                        if instance.fields.contains_key(&member) == false {
+                            let message =
+                                format!("{} has no field {}", instance.class.name, member);
+                            return Err(RuntimeError::new(&message).with_line(line));
+                        }
+                        instance.fields.insert(member, right_obj.clone());
+                        return Ok(right_obj);
+                    }
+                    Objects::Struct(s) => {
+                        let mut struct_value = s.borrow_mut();
+                        match struct_value.kind.fields.iter().position(|f| *f == member) {
+                            Some(i) => {
+                                struct_value.values[i] = right_obj.clone();
+                                return Ok(right_obj);
+                            }
+                            None => {
+                                let message =
+                                    format!("{} has no field {}", struct_value.kind.name, member);
+                                return Err(RuntimeError::new(&message).with_line(line));
+                            }
+                        }
+                    }
+                    _ => {
+                        return Err(RuntimeError::new(
+                            "only instances and structs have assignable fields",
+                        )
+                        .with_line(line))
+                    }
                 }
             }
             parser::Expression::ArrayLit { elements } => {
-                let mut elements_object: Vec<Objects> = Vec::new();
-                for element in elements {
-                    elements_object.push(self.eval_expression(element)?);
+                return Ok(Objects::Array(self.eval_elements(elements)?));
+            }
+            parser::Expression::NamedArgument { name: _, value: _ } => {
+                return Err(RuntimeError::new("named values are only allowed in calls"));
+            }
+            parser::Expression::PropagateExpr { value, line } => {
+                match self.eval_expression(*value)? {
+                    Objects::Result(Ok(s)) => return Ok(*s),
+                    Objects::Result(Err(s)) => {
+                        return Err(
+                            RuntimeError::early_return(Objects::Result(Err(s))).with_line(line)
+                        )
+                    }
+                    s => {
+                        let message = format!("? expects a result but got {}", s.type_name());
+                        return Err(RuntimeError::new(&message)
+                            .with_kind("TypeError")
+                            .with_line(line));
+                    }
+                }
+            }
+            parser::Expression::TryExpr {
+                body,
+                catch_name,
+                catch_body,
+                finally,
+            } => {
+                let mut result = self.eval_scoped_block(&body);
+                if let Some(catch_body) = catch_body {
+                    let caught = match &result {
+                        Err(e) if e.returned.is_none() => Some(e.to_object()),
+                        _ => None,
+                    };
+                    if let Some(caught) = caught {
+                        // the caught value is bound in a scope of its own
+                        let mut inner_environment =
+                            Environment::new_enclosed(self.environment.clone());
+                        if let Some(name) = catch_name {
+                            inner_environment.add(name, caught);
+                        }
+                        let outer_environment = mem::replace(
+                            &mut self.environment,
+                            Rc::new(RefCell::new(inner_environment)),
+                        );
+                        result = self.eval_block(&catch_body);
+                        self.environment = outer_environment;
+                    }
                 }
-                return Ok(Objects::Array(elements_object));
+                // finally always runs; an error or return inside it wins over
+                // the outcome of the try and catch blocks
+                if let Some(finally) = finally {
+                    self.eval_scoped_block(&finally)?;
+                }
+                return result;
+            }
+            parser::Expression::Spread { value: _ } => {
+                return Err(RuntimeError::new(
+                    "spreading is only allowed in calls, arrays and tuples",
+                ));
+            }
+            parser::Expression::TupleLit { elements } => {
+                return Ok(Objects::Tuple(self.eval_elements(elements)?));
             }
-            parser::Expression::IndexExpression { left, right } => {
+            parser::Expression::IndexExpression { left, right, line } => {
                 let left_obj = self.eval_expression(*left)?;
                 let right_obj = self.eval_expression(*right)?;
-                return self.eval_index_expression(left_obj, right_obj);
+                match self.eval_index_expression(left_obj, right_obj) {
+                    Ok(s) => return Ok(s),
+                    Err(e) => return Err(e.with_line(line)),
+                }
             }
             parser::Expression::HashMap { keys, values } => {
-                let hash = HashMap::new();
-                for (_i, _j) in keys.iter().zip(values) {
-                    //hash.insert(self.eval_expression(i.clone())?, self.eval_expression(j)?);
+                let mut hash = HashMap::new();
+                for (i, j) in keys.into_iter().zip(values) {
+                    let key = hash_key(self.eval_expression(i)?)?;
+                    hash.insert(key, self.eval_expression(j)?);
                 }
                 return Ok(Objects::Hash(hash));
             }
@@ -187,118 +645,729 @@ impl Evaluator {
                 identifier,
                 operator,
                 right,
+                line,
             } => {
-                let val = self.environment.search(identifier.clone());
+                let val = self.environment.borrow().search(identifier.clone());
                 let id_value;
                 match val {
-                    Some(s) => id_value = s.clone(),
-                    None => return Err("identifier not found"),
+                    Some(s) => id_value = s,
+                    None => return Err(not_found(&identifier).with_line(line)),
                 }
                 let right_obj = self.eval_expression(*right)?;
                 match operator {
-                    tokens::TokenTypes::CompoundOperator('+') => {
-                        let operator = tokens::TokenTypes::Operator('+');
+                    tokens::TokenTypes::CompoundOperator(s) => {
+                        let operator = tokens::TokenTypes::Operator(s);
                         let eval_expression =
                             self.eval_infix_expression(operator, id_value, right_obj);
                         match eval_expression {
                             Ok(s) => {
                                 return Ok(s);
                             }
-                            Err(e) => return Err(e),
+                            Err(e) => return Err(e.with_line(line)),
                         }
                     }
-                    _ => return Err("wrong compound operator"),
+                    _ => return Err(RuntimeError::new("wrong compound operator").with_line(line)),
                 }
             }
-            parser::Expression::VarChange { identifier, right } => {
-                let val = self.environment.search(identifier.clone());
-                match val {
-                    Some(_s) => {
-                        let right_obj = self.eval_expression(*right)?;
-                        self.environment.remove(identifier.clone());
-                        self.environment.add(identifier, right_obj.clone());
-                        return Ok(right_obj);
+            parser::Expression::VarChange {
+                identifier,
+                right,
+                line,
+            } => {
+                let right_obj = self.eval_expression(*right)?;
+                let assigned = self
+                    .environment
+                    .borrow_mut()
+                    .assign(identifier, right_obj.clone());
+                match assigned {
+                    Ok(_s) => return Ok(right_obj),
+                    Err(e) => return Err(e.with_line(line)),
+                }
+            }
+            parser::Expression::MatchExpr { value, arms, line } => {
+                let subject = self.eval_expression(*value)?;
+                for arm in arms {
+                    let mut bindings: Vec<(String, Objects)> = Vec::new();
+                    if self.match_pattern(&arm.pattern, &subject, &mut bindings)? == false {
+                        continue;
+                    }
+
+                    // bindings and the guard live in a scope of their own
+                    let mut inner_environment = Environment::new_enclosed(self.environment.clone());
+                    for (name, bound) in bindings {
+                        inner_environment.add(name, bound);
+                    }
+                    let outer_environment = mem::replace(
+                        &mut self.environment,
+                        Rc::new(RefCell::new(inner_environment)),
+                    );
+                    let guard_passed = match arm.guard {
+                        Some(guard) => self.eval_expression(guard).map(|g| g.is_truthy()),
+                        None => Ok(true),
+                    };
+                    let evaluated = match guard_passed {
+                        Ok(true) => Some(self.eval_block(&arm.body)),
+                        Ok(false) => None,
+                        Err(e) => Some(Err(e)),
+                    };
+                    self.environment = outer_environment;
+
+                    match evaluated {
+                        Some(s) => return s,
+                        None => continue,
                     }
-                    None => return Err("identifier not found"),
                 }
+                return Err(RuntimeError::new("no match arm matches the value").with_line(line));
             }
             parser::Expression::WhileExpr { condition, body } => {
-                let mut obj_condition;
                 loop {
-                    obj_condition = self.eval_expression(*condition.clone())?;
-                    match obj_condition {
-                        Objects::Boolean(true) => {
-                            for statement in *body.clone() {
-                                let evaluated_statement = Some(self.return_if_condition(statement.clone()));
-                                match statement {
-                                    parser::Statement::ReturnStatement { value: _ } => {
-                                        match evaluated_statement {
-                                            Some(s) => return Ok(s),
-                                            None => return Err("error evaluating if expressions"),
-                                        }
-                                    }
-                                    _ => continue,
+                    let obj_condition = self.eval_expression(*condition.clone())?;
+                    if obj_condition.is_truthy() == false {
+                        break;
+                    }
+                    self.eval_scoped_block(&body)?;
+                }
+                return Ok(Objects::Nil);
+            }
+        }
+    }
+
+    /// Loads a module once and returns it. Each module runs in its own top
+    /// level scope and only the names it exports become members.
+    fn import_module(&mut self, path: &str) -> Result<Objects, RuntimeError> {
+        let resolved = self.resolve_import(path)?;
+        if let Some(s) = self.modules.get(&resolved) {
+            return Ok(s.clone());
+        }
+        if self.loading.contains(&resolved) {
+            let mut cycle: Vec<String> = self
+                .loading
+                .iter()
+                .skip_while(|p| **p != resolved)
+                .map(|p| p.display().to_string())
+                .collect();
+            cycle.push(resolved.display().to_string());
+            let message = format!("import cycle: {}", cycle.join(" -> "));
+            return Err(RuntimeError::new(&message).with_kind("ImportError"));
+        }
+
+        let std_source = STD_MODULES
+            .iter()
+            .find(|(name, _source)| Some(*name) == path.strip_prefix("std/"));
+        let contents = match std_source {
+            Some((_name, source)) => Ok(source.to_string()),
+            None => fs::read_to_string(&resolved),
+        };
+        let contents = match contents {
+            Ok(s) => s,
+            Err(e) => {
+                let message = format!("couldn't read module {}: {}", path, e);
+                return Err(RuntimeError::new(&message).with_kind("ImportError"));
+            }
+        };
+        let (tokens, lines) = lexer::get_keywords(&contents);
+        let statements = match parser::Parser::new(tokens, lines).parse_token_line() {
+            Ok(s) => s,
+            Err(e) => {
+                let message = format!("couldn't parse module {}: {}", path, e);
+                return Err(RuntimeError::new(&message).with_kind("ImportError"));
+            }
+        };
+
+        let outer_environment = mem::replace(
+            &mut self.environment,
+            Rc::new(RefCell::new(Environment::new())),
+        );
+        let outer_file = self.current_file.replace(resolved.clone());
+        let outer_exports = mem::take(&mut self.exports);
+        self.loading.push(resolved.clone());
+
+        let evaluated = self.eval_block(&statements);
+
+        self.loading.pop();
+        let exports = mem::replace(&mut self.exports, outer_exports);
+        self.current_file = outer_file;
+        let module_environment = mem::replace(&mut self.environment, outer_environment);
+
+        match evaluated {
+            Err(e) if e.returned.is_none() => return Err(e.with_frame(path)),
+            _ => {}
+        }
+        let mut members = HashMap::new();
+        for name in exports {
+            if let Some(s) = module_environment.borrow().search(name.clone()) {
+                members.insert(name, s);
+            }
+        }
+        let module = Objects::Module(Rc::new(object::Module {
+            name: path.to_string(),
+            members,
+        }));
+        self.modules.insert(resolved, module.clone());
+        return Ok(module);
+    }
+
+    /// Finds an imported file next to the importing file, or else in the
+    /// search path. `.axel` is added to paths without an extension.
+    fn resolve_import(&self, path: &str) -> Result<PathBuf, RuntimeError> {
+        if let Some(name) = path.strip_prefix("std/") {
+            if STD_MODULES.iter().any(|(s, _source)| *s == name) {
+                return Ok(PathBuf::from(path));
+            }
+        }
+        let mut relative = PathBuf::from(path);
+        if relative.extension().is_none() {
+            relative.set_extension("axel");
+        }
+        let base = match &self.current_file {
+            Some(s) => s.parent().map(Path::to_path_buf).unwrap_or_default(),
+            None => PathBuf::new(),
+        };
+        let mut candidates = vec![base.join(&relative)];
+        for directory in &self.search_paths {
+            candidates.push(directory.join(&relative));
+        }
+        for candidate in candidates {
+            if let Ok(s) = candidate.canonicalize() {
+                if s.is_file() {
+                    return Ok(s);
+                }
+            }
+        }
+        let message = format!("module {} not found", path);
+        return Err(RuntimeError::new(&message).with_kind("ImportError"));
+    }
+
+    /// Checks `value` against `pattern`, collecting the names it binds.
+    fn match_pattern(
+        &mut self,
+        pattern: &parser::Pattern,
+        value: &Objects,
+        bindings: &mut Vec<(String, Objects)>,
+    ) -> Result<bool, RuntimeError> {
+        match pattern {
+            parser::Pattern::Literal { value: literal } => {
+                let literal_obj = self.eval_expression(literal.clone())?;
+                return Ok(&literal_obj == value);
+            }
+            parser::Pattern::Wildcard => return Ok(true),
+            parser::Pattern::Binding { name } => {
+                // a name of a variant without payloads compares against it
+                // instead of binding
+                if let Some(Objects::Variant(s)) = self.environment.borrow().search(name.clone()) {
+                    if s.values.is_empty() {
+                        return Ok(&Objects::Variant(s) == value);
+                    }
+                }
+                bindings.push((name.clone(), value.clone()));
+                return Ok(true);
+            }
+            parser::Pattern::Variant { name, elements } => {
+                // `ok(p)` and `err(p)` match result values unless the names
+                // are shadowed by enum variants
+                let variant = self.environment.borrow().search(name.clone());
+                if variant.is_none() && (name == "ok" || name == "err") && elements.len() == 1 {
+                    match value {
+                        Objects::Result(Ok(s)) if name == "ok" => {
+                            return self.match_pattern(&elements[0], s, bindings)
+                        }
+                        Objects::Result(Err(s)) if name == "err" => {
+                            return self.match_pattern(&elements[0], s, bindings)
+                        }
+                        _ => return Ok(false),
+                    }
+                }
+                let kind;
+                match variant {
+                    Some(Objects::Constructor(s)) => kind = s,
+                    Some(Objects::Variant(s)) => kind = s.kind,
+                    _ => {
+                        return Err(RuntimeError::new(&format!(
+                            "{} is not an enum variant",
+                            name
+                        )))
+                    }
+                }
+                if kind.fields.len() != elements.len() {
+                    let message = format!(
+                        "pattern {} has {} fields but the variant has {}",
+                        name,
+                        elements.len(),
+                        kind.fields.len()
+                    );
+                    return Err(RuntimeError::new(&message));
+                }
+                match value {
+                    Objects::Variant(s) if Rc::ptr_eq(&s.kind, &kind) => {
+                        for (element_pattern, element) in elements.iter().zip(&s.values) {
+                            if self.match_pattern(element_pattern, element, bindings)? == false {
+                                return Ok(false);
+                            }
+                        }
+                        return Ok(true);
+                    }
+                    _ => return Ok(false),
+                }
+            }
+            parser::Pattern::Array { elements } => match value {
+                Objects::Array(s) if s.len() == elements.len() => {
+                    for (element_pattern, element) in elements.iter().zip(s) {
+                        if self.match_pattern(element_pattern, element, bindings)? == false {
+                            return Ok(false);
+                        }
+                    }
+                    return Ok(true);
+                }
+                _ => return Ok(false),
+            },
+            parser::Pattern::Tuple { elements } => match value {
+                Objects::Tuple(s) if s.len() == elements.len() => {
+                    for (element_pattern, element) in elements.iter().zip(s) {
+                        if self.match_pattern(element_pattern, element, bindings)? == false {
+                            return Ok(false);
+                        }
+                    }
+                    return Ok(true);
+                }
+                _ => return Ok(false),
+            },
+            parser::Pattern::Hash { keys, values } => match value {
+                Objects::Hash(s) => {
+                    for (key, value_pattern) in keys.iter().zip(values) {
+                        let key_obj = self.eval_expression(key.clone())?;
+                        match s.get(&key_obj) {
+                            Some(t) => {
+                                if self.match_pattern(value_pattern, t, bindings)? == false {
+                                    return Ok(false);
                                 }
                             }
+                            None => return Ok(false),
                         }
-                        Objects::Boolean(false) => break,
-                        _ => return Err("evaluating while expression condition"),
                     }
+                    return Ok(true);
                 }
-                return Ok(obj_condition);
+                _ => return Ok(false),
+            },
+            parser::Pattern::Or { alternatives } => {
+                for alternative in alternatives {
+                    let mut alternative_bindings = Vec::new();
+                    if self.match_pattern(alternative, value, &mut alternative_bindings)? {
+                        bindings.append(&mut alternative_bindings);
+                        return Ok(true);
+                    }
+                }
+                return Ok(false);
             }
         }
     }
 
-    fn eval_index_expression<'a>(
+    fn eval_index_expression(
         &mut self,
         left: Objects,
         right: Objects,
-    ) -> Result<Objects, &'a str> {
+    ) -> Result<Objects, RuntimeError> {
         match (left, right) {
-            (Objects::Array(s), Objects::Integer(t)) => {
-                return Ok(s[t as usize].clone());
+            (Objects::Array(s), Objects::Integer(t)) => match s.get(t as usize) {
+                Some(v) if t >= 0 => return Ok(v.clone()),
+                _ => {
+                    let message =
+                        format!("index {} out of bounds for array of length {}", t, s.len());
+                    return Err(RuntimeError::new(&message).with_kind("IndexError"));
+                }
+            },
+            (Objects::String(s), Objects::Integer(t)) => match s.chars().nth(t as usize) {
+                Some(v) if t >= 0 => return Ok(Objects::String(v.to_string())),
+                _ => {
+                    let message = format!(
+                        "index {} out of bounds for string of length {}",
+                        t,
+                        s.chars().count()
+                    );
+                    return Err(RuntimeError::new(&message).with_kind("IndexError"));
+                }
+            },
+            (Objects::Tuple(s), Objects::Integer(t)) => match s.get(t as usize) {
+                Some(v) if t >= 0 => return Ok(v.clone()),
+                _ => {
+                    let message =
+                        format!("index {} out of bounds for tuple of length {}", t, s.len());
+                    return Err(RuntimeError::new(&message).with_kind("IndexError"));
+                }
+            },
+            (Objects::Hash(s), t) => match s.get(&t) {
+                Some(v) => return Ok(v.clone()),
+                None => return Ok(Objects::Nil),
+            },
+            _ => return Err(RuntimeError::new("Array call expressions unsupported")),
+        }
+    }
+
+    /// Calls a function, builtin or class with already evaluated positional
+    /// and named arguments. Errors about the call itself, such as a wrong
+    /// number of arguments, are given the line of the call; errors raised
+    /// inside a function body keep their own.
+    fn call_object(
+        &mut self,
+        callee: Objects,
+        param_values: Vec<Objects>,
+        named_values: NamedArguments,
+        line: Option<usize>,
+    ) -> Result<Objects, RuntimeError> {
+        let at_call = |e: RuntimeError| match line {
+            Some(s) => e.with_line(s),
+            None => e,
+        };
+        match &callee {
+            Objects::Function(_) | Objects::Class(_) => {}
+            _ if named_values.is_empty() == false => {
+                let message = format!("{} doesn't take named arguments", callee.type_name());
+                return Err(at_call(RuntimeError::new(&message)));
+            }
+            _ => {}
+        }
+        match callee {
+            Objects::Function(func) => {
+                // the body runs in a new scope enclosed by the scope the
+                // function was declared in, holding the parameters
+                let inner_environment = Environment::new_enclosed(func.environment.clone());
+                let outer_environment = mem::replace(
+                    &mut self.environment,
+                    Rc::new(RefCell::new(inner_environment)),
+                );
+
+                let body_result = match self.bind_parameters(&func, param_values, named_values) {
+                    Ok(()) => self.eval_block(&func.body),
+                    Err(e) => Err(at_call(e)),
+                };
+
+                self.environment = outer_environment;
+
+                // a function that falls off the end of its body returns nil
+                match body_result {
+                    Ok(_) => return Ok(Objects::Nil),
+                    Err(RuntimeError {
+                        returned: Some(s), ..
+                    }) => return Ok(*s),
+                    Err(e) => return Err(e.with_frame(&func.name)),
+                }
+            }
+            Objects::BuiltIn(func) => {
+                // a builtin has no lines of its own, so its errors point at
+                // the call
+                func.arity
+                    .check(&func.name, param_values.len())
+                    .map_err(at_call)?;
+                return (func.function)(self, param_values).map_err(at_call);
+            }
+            Objects::Class(class) => {
+                // inherited fields come first, so a subclass field of the same
+                // name replaces the default of its superclass
+                let mut chain: Vec<Rc<object::Class>> = Vec::new();
+                let mut current = Some(class.clone());
+                while let Some(s) = current {
+                    current = s.superclass.clone();
+                    chain.push(s);
+                }
+                let mut fields = HashMap::new();
+                for ancestor in chain.iter().rev() {
+                    for (name, default) in &ancestor.fields {
+                        fields.insert(name.clone(), default.clone());
+                    }
+                }
+                let instance = Rc::new(RefCell::new(object::Instance {
+                    class: class.clone(),
+                    fields,
+                }));
+                match class.find_method("init") {
+                    Some((owner, init)) => {
+                        let bound = self.bind_method(&owner, &init, instance.clone());
+                        self.call_object(bound, param_values, named_values, line)?;
+                    }
+                    None if param_values.is_empty() == false
+                        || named_values.is_empty() == false =>
+                    {
+                        let message =
+                            format!("{} has no init method to take arguments", class.name);
+                        return Err(at_call(RuntimeError::new(&message)));
+                    }
+                    None => {}
+                }
+                return Ok(Objects::Instance(instance));
+            }
+            Objects::Constructor(kind) => {
+                if param_values.len() != kind.fields.len() {
+                    let message = format!(
+                        "{} expects {} {} but got {}",
+                        kind.name,
+                        kind.fields.len(),
+                        object::arguments(kind.fields.len()),
+                        param_values.len()
+                    );
+                    return Err(at_call(RuntimeError::new(&message)));
+                }
+                return Ok(Objects::Variant(object::Variant {
+                    kind,
+                    values: param_values,
+                }));
+            }
+            _ => {
+                let error = RuntimeError::new("object isn't a function").with_kind("TypeError");
+                return Err(at_call(error));
             }
-            _ => return Err("Array call expressions unsupported"),
         }
     }
 
-    fn eval_call_params<'a>(
+    /// Looks up a field or method on an instance, searching methods through
+    /// the superclass chain. Methods come back bound to the instance, which
+    /// their body sees as `self` (or `this`).
+    fn eval_member(&mut self, obj: Objects, member: &str) -> Result<Objects, RuntimeError> {
+        match obj {
+            Objects::Instance(s) => {
+                let instance = s.borrow();
+                if let Some(value) = instance.fields.get(member) {
+                    return Ok(value.clone());
+                }
+                match instance.class.find_method(member) {
+                    Some((owner, method)) => {
+                        return Ok(self.bind_method(&owner, &method, s.clone()))
+                    }
+                    None => {
+                        let message = format!("{} has no member {}", instance.class.name, member);
+                        return Err(RuntimeError::new(&message));
+                    }
+                }
+            }
+            Objects::Struct(s) => {
+                let struct_value = s.borrow();
+                match struct_value.kind.fields.iter().position(|f| f == member) {
+                    Some(i) => return Ok(struct_value.values[i].clone()),
+                    None => {
+                        let message = format!("{} has no field {}", struct_value.kind.name, member);
+                        return Err(RuntimeError::new(&message));
+                    }
+                }
+            }
+            Objects::Module(s) => match s.members.get(member) {
+                Some(value) => return Ok(value.clone()),
+                None => {
+                    let message = format!("module {} doesn't export {}", s.name, member);
+                    return Err(RuntimeError::new(&message).with_kind("NameError"));
+                }
+            },
+            Objects::Error(s) => match member {
+                "kind" => return Ok(Objects::String(s.kind.clone())),
+                "message" => return Ok(Objects::String(s.message.clone())),
+                "line" => match s.line {
+                    Some(line) => return Ok(Objects::Integer(line as i32)),
+                    None => return Ok(Objects::Nil),
+                },
+                "trace" => {
+                    let trace = s.trace.iter().map(|t| Objects::String(t.clone()));
+                    return Ok(Objects::Array(trace.collect()));
+                }
+                _ => {
+                    let message = format!("error has no field {}", member);
+                    return Err(RuntimeError::new(&message));
+                }
+            },
+            Objects::Variant(s) => match s.kind.fields.iter().position(|f| f == member) {
+                Some(i) => return Ok(s.values[i].clone()),
+                None => {
+                    let message = format!("{} has no field {}", s.kind.name, member);
+                    return Err(RuntimeError::new(&message));
+                }
+            },
+            _ => {
+                let message = format!("{} has no field {}", obj.type_name(), member);
+                return Err(RuntimeError::new(&message));
+            }
+        }
+    }
+
+    fn bind_method(
+        &self,
+        owner: &Rc<object::Class>,
+        method: &Function,
+        instance: Rc<RefCell<object::Instance>>,
+    ) -> Objects {
+        let mut bound_environment = Environment::new_enclosed(method.environment.clone());
+        if let Some(s) = &owner.superclass {
+            bound_environment.add("super".to_string(), Objects::Class(s.clone()));
+        }
+        bound_environment.add("self".to_string(), Objects::Instance(instance.clone()));
+        bound_environment.add("this".to_string(), Objects::Instance(instance));
+        return Objects::Function(Function {
+            name: method.name.clone(),
+            parameters: method.parameters.clone(),
+            body: method.body.clone(),
+            environment: Rc::new(RefCell::new(bound_environment)),
+        });
+    }
+
+    /// Binds call arguments to the parameters of `func` in the current scope.
+    /// Positional arguments fill parameters in order and named ones by name;
+    /// parameters left out take their default, and a rest parameter collects
+    /// the remaining positional arguments into an array.
+    fn bind_parameters(
+        &mut self,
+        func: &Function,
+        param_values: Vec<Objects>,
+        named_values: NamedArguments,
+    ) -> Result<(), RuntimeError> {
+        let fixed: Vec<&parser::Parameter> = func.parameters.iter().filter(|p| !p.rest).collect();
+        let rest = func.parameters.iter().find(|p| p.rest);
+        if rest.is_none() && param_values.len() > fixed.len() {
+            let at_most = match fixed.iter().any(|p| p.default.is_some()) {
+                true => "at most ",
+                false => "",
+            };
+            let message = format!(
+                "{} expects {}{} {} but got {}",
+                func.name,
+                at_most,
+                fixed.len(),
+                object::arguments(fixed.len()),
+                param_values.len()
+            );
+            return Err(RuntimeError::new(&message));
+        }
+
+        let mut values: Vec<Option<Objects>> = vec![None; fixed.len()];
+        let mut extra: Vec<Objects> = Vec::new();
+        for (i, value) in param_values.into_iter().enumerate() {
+            if i < fixed.len() {
+                values[i] = Some(value);
+            } else {
+                extra.push(value);
+            }
+        }
+        for (name, value) in named_values {
+            match fixed.iter().position(|p| p.name == name) {
+                Some(i) if values[i].is_some() => {
+                    let message = format!("argument {} is given more than once", name);
+                    return Err(RuntimeError::new(&message));
+                }
+                Some(i) => values[i] = Some(value),
+                None => {
+                    let message = format!("{} has no parameter {}", func.name, name);
+                    return Err(RuntimeError::new(&message));
+                }
+            }
+        }
+
+        for (parameter, value) in fixed.iter().zip(values) {
+            let value = match value {
+                Some(s) => s,
+                None => match &parameter.default {
+                    Some(s) => self.eval_expression(s.clone())?,
+                    None => {
+                        let message = format!(
+                            "missing argument {} in call to {}",
+                            parameter.name, func.name
+                        );
+                        return Err(RuntimeError::new(&message));
+                    }
+                },
+            };
+            self.environment
+                .borrow_mut()
+                .add(parameter.name.clone(), value);
+        }
+        if let Some(s) = rest {
+            self.environment
+                .borrow_mut()
+                .add(s.name.clone(), Objects::Array(extra));
+        }
+        return Ok(());
+    }
+
+    /// Evaluates call arguments into positional values, with spread arrays
+    /// and tuples flattened in, and named values.
+    fn eval_call_params(
         &mut self,
         parameters: Vec<parser::Expression>,
-    ) -> Result<Vec<Objects>, &'a str> {
+    ) -> Result<(Vec<Objects>, NamedArguments), RuntimeError> {
         let mut result = Vec::new();
+        let mut named = Vec::new();
         for arg in parameters {
-            let arg_eval = self.eval_expression(arg);
-            match arg_eval {
-                Ok(s) => result.push(s),
-                Err(e) => return Err(e),
+            match arg {
+                parser::Expression::NamedArgument { name, value } => {
+                    if named.iter().any(|(n, _)| *n == name) {
+                        let message = format!("argument {} is given more than once", name);
+                        return Err(RuntimeError::new(&message));
+                    }
+                    named.push((name, self.eval_expression(*value)?));
+                }
+                parser::Expression::Spread { value } => {
+                    result.append(&mut self.eval_spread(*value)?);
+                }
+                _ if named.is_empty() == false => {
+                    return Err(RuntimeError::new(
+                        "positional arguments can't follow named arguments",
+                    ));
+                }
+                s => result.push(self.eval_expression(s)?),
+            }
+        }
+        return Ok((result, named));
+    }
+
+    /// Evaluates the elements of an array or tuple literal.
+    fn eval_elements(
+        &mut self,
+        elements: Vec<parser::Expression>,
+    ) -> Result<Vec<Objects>, RuntimeError> {
+        let mut result = Vec::new();
+        for element in elements {
+            match element {
+                parser::Expression::Spread { value } => {
+                    result.append(&mut self.eval_spread(*value)?);
+                }
+                parser::Expression::NamedArgument { name: _, value: _ } => {
+                    return Err(RuntimeError::new("named values are only allowed in calls"))
+                }
+                s => result.push(self.eval_expression(s)?),
             }
         }
         return Ok(result);
     }
 
-    fn eval_bang_operator<'a>(&mut self, obj: Objects) -> Result<Objects, &'a str> {
-        match obj {
-            Objects::Boolean(true) => return Ok(Objects::Boolean(true)),
-            Objects::Boolean(false) => return Ok(Objects::Boolean(false)),
-            _ => return Err("expected boolean object"),
+    fn eval_spread(&mut self, value: parser::Expression) -> Result<Vec<Objects>, RuntimeError> {
+        match self.eval_expression(value)? {
+            Objects::Array(s) | Objects::Tuple(s) => return Ok(s),
+            s => {
+                let message = format!("cannot spread {}, only arrays and tuples", s.type_name());
+                return Err(RuntimeError::new(&message));
+            }
         }
     }
 
-    fn eval_minus_operator<'a>(&mut self, obj: Objects) -> Result<Objects, &'a str> {
+    fn eval_bang_operator(&mut self, obj: Objects) -> Result<Objects, RuntimeError> {
+        return Ok(Objects::Boolean(!obj.is_truthy()));
+    }
+
+    fn eval_minus_operator(&mut self, obj: Objects) -> Result<Objects, RuntimeError> {
         match obj {
-            Objects::Integer(s) => return Ok(Objects::Integer(-s)),
+            Objects::Integer(s) => match s.checked_neg() {
+                Some(t) => return Ok(Objects::Integer(t)),
+                None => {
+                    return Err(RuntimeError::new("integer overflow").with_kind("ArithmeticError"))
+                }
+            },
             Objects::Float(s) => return Ok(Objects::Float(-s)),
-            _ => return Err("expected an integer or float to the right of - sign"),
+            _ => {
+                return Err(RuntimeError::new(
+                    "expected an integer or float to the right of - sign",
+                ))
+            }
         }
     }
 
-    fn eval_prefix_expression<'a>(
+    fn eval_prefix_expression(
         &mut self,
         operator: tokens::TokenTypes,
         obj: Objects,
-    ) -> Result<Objects, &'a str> {
+    ) -> Result<Objects, RuntimeError> {
         match operator {
             tokens::TokenTypes::Bang => {
                 let bang_op = self.eval_bang_operator(obj);
@@ -314,27 +1383,72 @@ impl Evaluator {
                     Err(e) => return Err(e),
                 }
             }
-            _ => return Err("operator prefix mismatch"),
+            _ => return Err(RuntimeError::new("operator prefix mismatch")),
         }
     }
 
-    fn eval_infix_expression<'a>(
+    fn eval_infix_expression(
         &mut self,
         operator: tokens::TokenTypes,
         left: Objects,
         right: Objects,
-    ) -> Result<Objects, &'a str> {
+    ) -> Result<Objects, RuntimeError> {
+        match operator {
+            tokens::TokenTypes::Compare(tokens::Comparison::Equal) => {
+                return Ok(Objects::Boolean(left == right));
+            }
+            tokens::TokenTypes::Compare(tokens::Comparison::NotEqual) => {
+                return Ok(Objects::Boolean(left != right));
+            }
+            tokens::TokenTypes::Keywords(tokens::Keywords::Is) => match (left, right) {
+                (Objects::Instance(s), Objects::Class(t)) => {
+                    return Ok(Objects::Boolean(s.borrow().class.is_subclass_of(&t)));
+                }
+                (_, Objects::Class(_)) => return Ok(Objects::Boolean(false)),
+                _ => return Err(RuntimeError::new("right side of is must be a class")),
+            },
+            _ => {}
+        }
+        // an integer meeting a float is promoted, so 1 + 0.5 is 1.5
+        let (left, right) = match (left, right) {
+            (Objects::Integer(s), Objects::Float(r)) => {
+                (Objects::Float(s as f32), Objects::Float(r))
+            }
+            (Objects::Float(s), Objects::Integer(r)) => {
+                (Objects::Float(s), Objects::Float(r as f32))
+            }
+            pair => pair,
+        };
         match (left, right) {
             (Objects::Integer(s), Objects::Integer(r)) => match operator {
-                tokens::TokenTypes::Operator('+') => return Ok(Objects::Integer(s + r)),
-                tokens::TokenTypes::Operator('-') => return Ok(Objects::Integer(s - r)),
-                tokens::TokenTypes::Operator('*') => return Ok(Objects::Integer(s * r)),
-                tokens::TokenTypes::Operator('/') => return Ok(Objects::Integer(s / r)),
-                tokens::TokenTypes::Compare(tokens::Comparison::Equal) => {
-                    return Ok(Objects::Boolean(s == r));
+                tokens::TokenTypes::Operator('+') => return integer_result(s.checked_add(r)),
+                tokens::TokenTypes::Operator('-') => return integer_result(s.checked_sub(r)),
+                tokens::TokenTypes::Operator('*') => return integer_result(s.checked_mul(r)),
+                tokens::TokenTypes::Operator('/') => {
+                    if r == 0 {
+                        return Err(
+                            RuntimeError::new("division by zero").with_kind("ArithmeticError")
+                        );
+                    }
+                    return integer_result(s.checked_div(r));
+                }
+                tokens::TokenTypes::Operator('%') => {
+                    if r == 0 {
+                        return Err(
+                            RuntimeError::new("modulo by zero").with_kind("ArithmeticError")
+                        );
+                    }
+                    return integer_result(s.checked_rem(r));
                 }
-                tokens::TokenTypes::Compare(tokens::Comparison::NotEqual) => {
-                    return Ok(Objects::Boolean(s != r));
+                tokens::TokenTypes::Shift(t) => {
+                    if r < 0 || r >= i32::BITS as i32 {
+                        return Err(RuntimeError::new("shift amount out of range")
+                            .with_kind("ArithmeticError"));
+                    }
+                    match t {
+                        '<' => return Ok(Objects::Integer(s << r)),
+                        _ => return Ok(Objects::Integer(s >> r)),
+                    }
                 }
                 tokens::TokenTypes::Compare(tokens::Comparison::Less) => {
                     return Ok(Objects::Boolean(s < r));
@@ -348,19 +1462,16 @@ impl Evaluator {
                 tokens::TokenTypes::Compare(tokens::Comparison::GreaterE) => {
                     return Ok(Objects::Boolean(s >= r));
                 }
-                _ => return Err("unknown operator"),
+                _ => return Err(RuntimeError::new("unknown operator")),
             },
             (Objects::Float(s), Objects::Float(r)) => match operator {
                 tokens::TokenTypes::Operator('+') => return Ok(Objects::Float(s + r)),
                 tokens::TokenTypes::Operator('-') => return Ok(Objects::Float(s - r)),
                 tokens::TokenTypes::Operator('*') => return Ok(Objects::Float(s * r)),
+                // Float division and remainder follow IEEE 754: dividing by zero
+                // yields inf, -inf or NaN instead of a runtime error.
                 tokens::TokenTypes::Operator('/') => return Ok(Objects::Float(s / r)),
-                tokens::TokenTypes::Compare(tokens::Comparison::Equal) => {
-                    return Ok(Objects::Boolean(s == r));
-                }
-                tokens::TokenTypes::Compare(tokens::Comparison::NotEqual) => {
-                    return Ok(Objects::Boolean(s != r));
-                }
+                tokens::TokenTypes::Operator('%') => return Ok(Objects::Float(s % r)),
                 tokens::TokenTypes::Compare(tokens::Comparison::Less) => {
                     return Ok(Objects::Boolean(s < r));
                 }
@@ -373,92 +1484,100 @@ impl Evaluator {
                 tokens::TokenTypes::Compare(tokens::Comparison::GreaterE) => {
                     return Ok(Objects::Boolean(s >= r));
                 }
-                _ => return Err("unknown operator"),
+                _ => return Err(RuntimeError::new("unknown operator")),
             },
+            // Strings compare by content; the ordering of Rust strings is
+            // lexicographic over UTF-8 bytes, which matches Unicode code point order.
             (Objects::String(s), Objects::String(t)) => match operator {
                 tokens::TokenTypes::Operator('+') => return Ok(Objects::String(s + &t)),
-                tokens::TokenTypes::Compare(tokens::Comparison::Equal) => {
-                    return Ok(Objects::Boolean(s.len() == t.len()));
-                }
-                tokens::TokenTypes::Compare(tokens::Comparison::NotEqual) => {
-                    return Ok(Objects::Boolean(s.len() != t.len()));
-                }
                 tokens::TokenTypes::Compare(tokens::Comparison::Less) => {
-                    return Ok(Objects::Boolean(s.len() < t.len()));
+                    return Ok(Objects::Boolean(s < t));
                 }
                 tokens::TokenTypes::Compare(tokens::Comparison::LessE) => {
-                    return Ok(Objects::Boolean(s.len() <= t.len()));
+                    return Ok(Objects::Boolean(s <= t));
                 }
                 tokens::TokenTypes::Compare(tokens::Comparison::Greater) => {
-                    return Ok(Objects::Boolean(s.len() > t.len()));
+                    return Ok(Objects::Boolean(s > t));
                 }
                 tokens::TokenTypes::Compare(tokens::Comparison::GreaterE) => {
-                    return Ok(Objects::Boolean(s.len() >= t.len()));
+                    return Ok(Objects::Boolean(s >= t));
                 }
-                _ => return Err("unknown operator"),
+                _ => return Err(RuntimeError::new("unknown operator")),
             },
-            _ => return Err("operand type mismatch"),
+            _ => return Err(RuntimeError::new("operand type mismatch").with_kind("TypeError")),
         }
     }
 
-    fn evaluate_if_condition<'a>(
+    fn evaluate_if_condition(
         &mut self,
         condition: parser::Expression,
         then: Vec<parser::Statement>,
         other: Option<Box<Vec<parser::Statement>>>,
-    ) -> Result<Objects, &'a str> {
+    ) -> Result<Objects, RuntimeError> {
         let obj_condition = self.eval_expression(condition.clone())?;
-        match obj_condition {
-            Objects::Boolean(true) => {
-                let mut evaluated_statement = None;
-                for statement in then {
-                    evaluated_statement = Some(self.return_if_condition(statement.clone()));
-                    match statement {
-                        parser::Statement::ReturnStatement { value: _ } => {
-                            match evaluated_statement {
-                                Some(s) => return Ok(s),
-                                None => return Err("error evaluating if expressions"),
-                            }
-                        }
-                        _ => continue,
-                    }
-                }
-                match evaluated_statement {
-                    Some(s) => return Ok(s),
-                    None => return Err("error evaluating if expressions"),
-                }
-            }
-            Objects::Boolean(false) => match other {
-                Some(other_statement) => {
-                    let mut evaluated_statement = None;
-                    for statement in *other_statement {
-                        evaluated_statement = Some(self.return_if_condition(statement.clone()));
-                        match statement {
-                            parser::Statement::ReturnStatement { value: _ } => {
-                                match evaluated_statement {
-                                    Some(t) => return Ok(t),
-                                    None => return Err("error evaluating if expressions"),
-                                }
-                            }
-                            _ => continue,
-                        }
-                    }
-                    match evaluated_statement {
-                        Some(t) => return Ok(t),
-                        None => return Err("error evaluating if expressions"),
-                    }
-                }
-                _ => return Ok(Objects::Boolean(false)),
-            },
-            _ => return Err("evaluating if expression"),
+        if obj_condition.is_truthy() {
+            return self.eval_scoped_block(&then);
+        }
+        match other {
+            Some(other_statement) => return self.eval_scoped_block(&other_statement),
+            None => return Ok(Objects::Nil),
+        }
+    }
+
+    /// Evaluates statements in order and yields the last value, or nil for an
+    /// empty block. A `return` unwinds as an early return error, so enclosing
+    /// blocks and expressions stop as well.
+    fn eval_block(&mut self, statements: &[parser::Statement]) -> Result<Objects, RuntimeError> {
+        let mut evaluated = Objects::Nil;
+        for statement in statements {
+            evaluated = self.eval_statement(statement.clone())?;
         }
+        return Ok(evaluated);
     }
 
-    fn return_if_condition(&mut self, ifexpression: parser::Statement) -> Objects {
-        let st = self.eval_statement(ifexpression);
-        match st {
-            Ok(s) => return s,
-            _ => return Objects::Boolean(false),
+    /// Evaluates an `if` or `while` body in its own scope, so declarations
+    /// inside it don't outlive the block.
+    fn eval_scoped_block(
+        &mut self,
+        statements: &[parser::Statement],
+    ) -> Result<Objects, RuntimeError> {
+        let inner_environment = Environment::new_enclosed(self.environment.clone());
+        let outer_environment = mem::replace(
+            &mut self.environment,
+            Rc::new(RefCell::new(inner_environment)),
+        );
+        let evaluated = self.eval_block(statements);
+        self.environment = outer_environment;
+        return evaluated;
+    }
+}
+
+/// Rejects keys holding a struct: structs are shared and mutable, so a change
+/// to one would leave it stored under a stale hash.
+fn hash_key(key: Objects) -> Result<Objects, RuntimeError> {
+    fn holds_struct(value: &Objects) -> bool {
+        match value {
+            Objects::Struct(_) => true,
+            Objects::Array(s) | Objects::Tuple(s) => s.iter().any(holds_struct),
+            Objects::Variant(s) => s.values.iter().any(holds_struct),
+            Objects::Result(Ok(s)) | Objects::Result(Err(s)) => holds_struct(s),
+            _ => false,
         }
     }
+    if holds_struct(&key) {
+        return Err(RuntimeError::new("structs can't be used as hash keys").with_kind("TypeError"));
+    }
+    return Ok(key);
+}
+
+fn integer_result(value: Option<i32>) -> Result<Objects, RuntimeError> {
+    match value {
+        Some(s) => return Ok(Objects::Integer(s)),
+        None => return Err(RuntimeError::new("integer overflow").with_kind("ArithmeticError")),
+    }
+}
+
+fn not_found(name: &str) -> RuntimeError {
+    let message = format!("identifier {} not found", name);
+    return RuntimeError::new(&message).with_kind("NameError");
 }
@@ -1,32 +1,35 @@
 use crate::tokens;
 
-pub fn get_keywords(read_text: &String) -> Vec<tokens::TokenTypes> {
+/// Splits the source text into tokens, returning alongside them the line
+/// number (starting at 1) on which each token begins.
+pub fn get_keywords(read_text: &str) -> (Vec<tokens::TokenTypes>, Vec<usize>) {
     let text_vec: Vec<char> = read_text.chars().collect();
-    let final_vector = read_token(&text_vec);
-    final_vector
+    read_token(&text_vec)
 }
 
-fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
+fn read_token(text_vec: &[char]) -> (Vec<tokens::TokenTypes>, Vec<usize>) {
     let mut index = 0;
+    let mut line = 1;
     let mut token_vector = Vec::new();
+    let mut line_vector = Vec::new();
     loop {
+        while line_vector.len() < token_vector.len() {
+            line_vector.push(line);
+        }
         if index >= text_vec.len() {
             token_vector.push(tokens::TokenTypes::EndOfLine);
+            line_vector.push(line);
             break;
         }
         let mut chr = text_vec[index];
-        while is_ignored(chr) {
+        if is_ignored(chr) {
             index += 1;
-            if index > text_vec.len() {
-                token_vector.push(tokens::TokenTypes::EndOfLine);
-                break;
-            }
-            chr = text_vec[index];
+            continue;
         }
         match chr {
             '+' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::CompoundOperator('+'));
                 } else {
@@ -35,7 +38,7 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
             }
             '-' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::CompoundOperator('-'));
                 } else {
@@ -44,34 +47,43 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
             }
             '=' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::Compare(tokens::Comparison::Equal));
+                } else if text_vec.get(final_index) == Some(&'>') {
+                    index += 1;
+                    token_vector.push(tokens::TokenTypes::FatArrow);
                 } else {
                     token_vector.push(tokens::TokenTypes::Operator('='));
                 }
             }
             '<' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::Compare(tokens::Comparison::LessE));
+                } else if text_vec.get(final_index) == Some(&'<') {
+                    index += 1;
+                    token_vector.push(tokens::TokenTypes::Shift('<'));
                 } else {
                     token_vector.push(tokens::TokenTypes::Compare(tokens::Comparison::Less));
                 }
             }
             '>' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::Compare(tokens::Comparison::GreaterE));
+                } else if text_vec.get(final_index) == Some(&'>') {
+                    index += 1;
+                    token_vector.push(tokens::TokenTypes::Shift('>'));
                 } else {
                     token_vector.push(tokens::TokenTypes::Compare(tokens::Comparison::Greater));
                 }
             }
             '!' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::Compare(tokens::Comparison::NotEqual));
                 } else {
@@ -80,7 +92,7 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
             }
             '*' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::CompoundOperator('*'));
                 } else {
@@ -89,13 +101,45 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
             }
             '/' => {
                 let final_index = index + 1;
-                if text_vec[final_index] == '=' {
+                if text_vec.get(final_index) == Some(&'=') {
                     index += 1;
                     token_vector.push(tokens::TokenTypes::CompoundOperator('/'));
+                } else if text_vec.get(final_index) == Some(&'/') {
+                    // line comments run up to the end of the line
+                    while index + 1 < text_vec.len() && text_vec[index + 1] != '\n' {
+                        index += 1;
+                    }
                 } else {
                     token_vector.push(tokens::TokenTypes::Operator('/'));
                 }
             }
+            '%' => {
+                let final_index = index + 1;
+                if text_vec.get(final_index) == Some(&'=') {
+                    index += 1;
+                    token_vector.push(tokens::TokenTypes::CompoundOperator('%'));
+                } else {
+                    token_vector.push(tokens::TokenTypes::Operator('%'));
+                }
+            }
+            '&' => {
+                let final_index = index + 1;
+                if text_vec.get(final_index) == Some(&'&') {
+                    index += 1;
+                    token_vector.push(tokens::TokenTypes::And);
+                } else {
+                    token_vector.push(tokens::TokenTypes::Illegal);
+                }
+            }
+            '|' => {
+                let final_index = index + 1;
+                if text_vec.get(final_index) == Some(&'|') {
+                    index += 1;
+                    token_vector.push(tokens::TokenTypes::Or);
+                } else {
+                    token_vector.push(tokens::TokenTypes::Pipe);
+                }
+            }
             '(' => {
                 token_vector.push(tokens::TokenTypes::Operator('('));
             }
@@ -123,20 +167,40 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
             ':' => {
                 token_vector.push(tokens::TokenTypes::Colon);
             }
+            '?' => {
+                token_vector.push(tokens::TokenTypes::Question);
+            }
+            '.' => {
+                if text_vec.get(index + 1) == Some(&'.') && text_vec.get(index + 2) == Some(&'.') {
+                    index += 2;
+                    token_vector.push(tokens::TokenTypes::Ellipsis);
+                } else {
+                    token_vector.push(tokens::TokenTypes::Dot);
+                }
+            }
             ';' => {
                 token_vector.push(tokens::TokenTypes::Semicolon);
             }
             '"' => {
                 let mut final_index = index;
-                final_index += 1;
+                let mut closed = false;
                 loop {
                     final_index += 1;
-                    chr = text_vec[final_index];
+                    if final_index >= text_vec.len() {
+                        break;
+                    }
+                    chr = text_vec.get(final_index).copied().unwrap_or('\0');
                     if chr == '"' {
                         final_index += 1;
+                        closed = true;
                         break;
                     }
                 }
+                if closed == false {
+                    token_vector.push(tokens::TokenTypes::Illegal);
+                    index = text_vec.len();
+                    continue;
+                }
                 let string = &text_vec[index + 1..final_index - 1]
                     .iter()
                     .collect::<String>()
@@ -149,21 +213,21 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
                 //Ignore this
             }
             '\n' => {
-                //Ignore this
+                line += 1;
             }
             _ => {
                 if is_valid_number(chr) == true {
                     let mut final_index = index;
                     while is_valid_number(chr) == true {
                         final_index += 1;
-                        chr = text_vec[final_index];
+                        chr = text_vec.get(final_index).copied().unwrap_or('\0');
                     }
                     if chr == '.' {
                         final_index += 1;
-                        chr = text_vec[final_index];
+                        chr = text_vec.get(final_index).copied().unwrap_or('\0');
                         while is_valid_number(chr) == true {
                             final_index += 1;
-                            chr = text_vec[final_index];
+                            chr = text_vec.get(final_index).copied().unwrap_or('\0');
                         }
                         let identifier: &str =
                             &text_vec[index..final_index].iter().collect::<String>();
@@ -178,9 +242,10 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
                     index = final_index - 1;
                 } else if is_valid_identifier(chr) == true {
                     let mut final_index = index;
-                    while is_valid_identifier(chr) == true {
+                    // identifiers start with a letter or _ and may go on with digits
+                    while is_valid_identifier(chr) == true || is_valid_number(chr) == true {
                         final_index += 1;
-                        chr = text_vec[final_index];
+                        chr = text_vec.get(final_index).copied().unwrap_or('\0');
                     }
                     let identifier: &str = &text_vec[index..final_index].iter().collect::<String>();
                     match identifier {
@@ -193,7 +258,8 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
                         }
 
                         "while" => {
-                            token_vector.push(tokens::TokenTypes::Keywords(tokens::Keywords::While));
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::While));
                         }
 
                         "var" => {
@@ -201,7 +267,50 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
                         }
 
                         "class" => {
-                            token_vector.push(tokens::TokenTypes::Keywords(tokens::Keywords::Class));
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Class));
+                        }
+
+                        "enum" => {
+                            token_vector.push(tokens::TokenTypes::Keywords(tokens::Keywords::Enum));
+                        }
+
+                        "struct" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Struct));
+                        }
+
+                        "throw" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Throw));
+                        }
+
+                        "try" => {
+                            token_vector.push(tokens::TokenTypes::Keywords(tokens::Keywords::Try));
+                        }
+
+                        "catch" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Catch));
+                        }
+
+                        "finally" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Finally));
+                        }
+
+                        "import" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Import));
+                        }
+
+                        "export" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Export));
+                        }
+
+                        "as" => {
+                            token_vector.push(tokens::TokenTypes::Keywords(tokens::Keywords::As));
                         }
 
                         "const" => {
@@ -227,6 +336,24 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
                             token_vector
                                 .push(tokens::TokenTypes::Keywords(tokens::Keywords::False));
                         }
+
+                        "match" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Match));
+                        }
+
+                        "nil" => {
+                            token_vector.push(tokens::TokenTypes::Keywords(tokens::Keywords::Nil));
+                        }
+
+                        "super" => {
+                            token_vector
+                                .push(tokens::TokenTypes::Keywords(tokens::Keywords::Super));
+                        }
+
+                        "is" | "instanceof" => {
+                            token_vector.push(tokens::TokenTypes::Keywords(tokens::Keywords::Is));
+                        }
                         _ => {
                             token_vector
                                 .push(tokens::TokenTypes::Identifier(identifier.to_string()));
@@ -240,7 +367,7 @@ fn read_token(text_vec: &Vec<char>) -> Vec<tokens::TokenTypes> {
         }
         index += 1;
     }
-    token_vector
+    (token_vector, line_vector)
 }
 
 fn is_ignored(chr: char) -> bool {
@@ -248,9 +375,9 @@ fn is_ignored(chr: char) -> bool {
 }
 
 fn is_valid_number(chr: char) -> bool {
-    '0' <= chr && chr <= '9'
+    chr.is_ascii_digit()
 }
 
 fn is_valid_identifier(chr: char) -> bool {
-    'a' <= chr && chr <= 'z' || 'A' <= chr && chr <= 'Z' || chr == '_'
+    chr.is_ascii_alphabetic() || chr == '_'
 }
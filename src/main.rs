@@ -1,7 +1,20 @@
+#![allow(
+    clippy::needless_return,
+    clippy::bool_comparison,
+    clippy::needless_late_init,
+    clippy::op_ref,
+    clippy::box_collection,
+    clippy::enum_variant_names,
+    clippy::match_like_matches_macro,
+    // Objects hash functions, classes and instances by identity, never
+    // through their mutable state; mutable structs are refused as hash keys
+    clippy::mutable_key_type
+)]
 use std::env;
 use std::fs;
 use std::io::Write;
 use std::{io, path::PathBuf};
+mod builtins;
 mod evaluate;
 mod lexer;
 mod object;
@@ -17,34 +30,47 @@ fn main() {
     println!("OS: {}", env::consts::OS);
     println!("Write exit to stop the program");
     println!("Write read to parse a file");
+    println!("Write builtins to list the builtin functions");
     let args: Vec<String> = env::args().collect();
     let mut input = String::new();
 
-    if args.len() > 1 {
-        let contents = fs::read_to_string(&args[1]).expect("Couldn't read the file");
+    // imports not found next to the importing file are searched for in the
+    // AXEL_PATH directories and in every --path given on the command line
+    let mut file: Option<String> = None;
+    let mut prelude = true;
+    let mut search_paths: Vec<PathBuf> = Vec::new();
+    if let Some(s) = env::var_os("AXEL_PATH") {
+        search_paths.extend(env::split_paths(&s));
+    }
+    let mut arg_index = 1;
+    while arg_index < args.len() {
+        match args[arg_index].as_str() {
+            "--path" => {
+                arg_index += 1;
+                match args.get(arg_index) {
+                    Some(s) => search_paths.push(PathBuf::from(s)),
+                    None => println!("--path expects a directory"),
+                }
+            }
+            "--no-prelude" => prelude = false,
+            s => file = Some(s.to_string()),
+        }
+        arg_index += 1;
+    }
+
+    if let Some(file) = &file {
+        let contents = fs::read_to_string(file).expect("Couldn't read the file");
 
-        let token = lexer::get_keywords(&contents);
-        let mut parser = Parser::new(token);
-        let mut evaluator = Evaluator::new();
+        let (token, lines) = lexer::get_keywords(&contents);
+        let mut parser = Parser::new(token, lines);
+        let mut evaluator = new_evaluator(&search_paths, prelude);
+        evaluator.set_file(PathBuf::from(file));
         let result = parser.parse_token_line();
         match result {
-            Ok(s) => {
-                let mut obj_vector = Vec::new();
-                for st in s {
-                    let comp = st.clone();
-                    let eval = evaluator.eval_statement(st);
-                    obj_vector.push(eval);
-                    match comp {
-                        parser::Statement::ReturnStatement { value: _ } => break,
-                        _ => continue,
-                    }
-                }
-                let len = obj_vector.len() - 1;
-                match &obj_vector[len] {
-                    Ok(s) => println!("Evaluation success: {}", s),
-                    Err(e) => println!("Evaluation error: {}", e),
-                }
-            }
+            Ok(s) => match evaluator.eval_program(s) {
+                Ok(s) => println!("Evaluation success: {}", s),
+                Err(e) => println!("Evaluation error: {}", e),
+            },
             Err(e) => println!("Exit program with error: {}", e),
         }
     }
@@ -62,54 +88,62 @@ fn main() {
         io::stdin()
             .read_line(&mut input)
             .expect("Failed to read line");
-        let compare_input: String = input.trim_end_matches("\r\n").to_string();
+        let compare_input: String = input.trim().to_string();
         if compare_input == "exit" {
             println!("Goodbye!");
             break;
+        } else if compare_input == "builtins" {
+            let evaluator = Evaluator::new();
+            for builtin in evaluator.builtins().iter() {
+                println!("{}/{}: {}", builtin.name, builtin.arity, builtin.doc);
+            }
+            for (name, value) in evaluator.builtins().constants() {
+                println!("{} = {}", name, value);
+            }
         } else if compare_input == "read" {
             let mut file_content = String::new();
             println!("Write the file's path: ");
             io::stdin()
                 .read_line(&mut file_content)
                 .expect("Failed to read file");
-            let contents = fs::read_to_string(&args[1]).expect("Couldn't read the file");
+            let contents = fs::read_to_string(file_content.trim()).expect("Couldn't read the file");
 
-            let token = lexer::get_keywords(&contents);
-            let mut parser = Parser::new(token);
+            let (token, lines) = lexer::get_keywords(&contents);
+            let mut parser = Parser::new(token, lines);
             let result = parser.check_statement();
             match result {
                 Ok(_s) => println!("Program success"),
                 Err(e) => println!("Exit program with error: {}", e),
             }
         } else {
-            let token = lexer::get_keywords(&input);
-            let mut parser = Parser::new(token);
-            let mut evaluator = Evaluator::new();
+            let (token, lines) = lexer::get_keywords(&input);
+            let mut parser = Parser::new(token, lines);
+            let mut evaluator = new_evaluator(&search_paths, prelude);
             let result = parser.parse_token_line();
             match result {
-                Ok(s) => {
-                    let mut obj_vector = Vec::new();
-                    for st in s {
-                        let comp = st.clone();
-                        let eval = evaluator.eval_statement(st);
-                        obj_vector.push(eval);
-                        match comp {
-                            parser::Statement::ReturnStatement { value: _ } => break,
-                            _ => continue,
-                        }
-                    }
-                    let len = obj_vector.len() - 1;
-                    match &obj_vector[len] {
-                        Ok(s) => println!("Evaluation success: {}", s),
-                        Err(e) => println!("Evaluation error: {}", e),
-                    }
-                }
+                Ok(s) => match evaluator.eval_program(s) {
+                    Ok(s) => println!("Evaluation success: {}", s),
+                    Err(e) => println!("Evaluation error: {}", e),
+                },
                 Err(e) => println!("Exit program with error: {}", e),
             }
         }
     }
 }
 
+fn new_evaluator(search_paths: &[PathBuf], prelude: bool) -> Evaluator {
+    let mut evaluator = Evaluator::new();
+    for path in search_paths {
+        evaluator.add_search_path(path.clone());
+    }
+    if prelude {
+        if let Err(e) = evaluator.load_prelude() {
+            println!("Couldn't load the prelude: {}", e);
+        }
+    }
+    return evaluator;
+}
+
 fn get_path() -> std::io::Result<PathBuf> {
     let current_path = env::current_dir()?;
     Ok(current_path)
@@ -1,6 +1,11 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::rc::Rc;
 
+use crate::builtins::{self, BuiltinFunction};
 use crate::parser;
 
 #[derive(Debug, Clone)]
@@ -12,139 +17,434 @@ pub enum Objects {
     Function(Function),
     BuiltIn(BuiltinFunction),
     Array(Vec<Objects>),
-    Hash(HashMap<Objects, Objects>)
+    Tuple(Vec<Objects>),
+    Hash(HashMap<Objects, Objects>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    Constructor(Rc<VariantType>),
+    Variant(Variant),
+    StructType(Rc<StructType>),
+    Struct(Rc<RefCell<Struct>>),
+    Error(Rc<ErrorValue>),
+    Result(Result<Box<Objects>, Box<Objects>>),
+    Module(Rc<Module>),
+    Nil,
 }
 
-#[derive(Debug, Clone)]
-pub struct BuiltinFunction {
-    pub name: String,
+impl Objects {
+    /// Truthiness used by `!`, `if`, `while`, `&&` and `||`: `false`, `nil`,
+    /// `0`, `0.0`, `""`, `[]`, `()` and `{}` are falsy, every other value is truthy.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Objects::Boolean(s) => *s,
+            Objects::Integer(s) => *s != 0,
+            Objects::Float(s) => *s != 0.0,
+            Objects::String(s) => s.is_empty() == false,
+            Objects::Array(s) | Objects::Tuple(s) => s.is_empty() == false,
+            Objects::Hash(s) => s.is_empty() == false,
+            Objects::Nil => false,
+            Objects::Function(_) | Objects::BuiltIn(_) => true,
+            Objects::Class(_) | Objects::Instance(_) => true,
+            Objects::Constructor(_) | Objects::Variant(_) => true,
+            Objects::StructType(_) | Objects::Struct(_) => true,
+            Objects::Error(_) | Objects::Result(_) | Objects::Module(_) => true,
+        }
+    }
+
+    /// An `ok(value)` result.
+    pub fn ok(value: Objects) -> Objects {
+        Objects::Result(Ok(Box::new(value)))
+    }
+
+    /// An `err(value)` result, which builtins return to report a failure the
+    /// script can handle without aborting.
+    pub fn err(value: Objects) -> Objects {
+        Objects::Result(Err(Box::new(value)))
+    }
+
+    /// The name of the kind of object, as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Objects::Integer(_) => "integer",
+            Objects::Float(_) => "float",
+            Objects::String(_) => "string",
+            Objects::Boolean(_) => "boolean",
+            Objects::Function(_) | Objects::BuiltIn(_) => "function",
+            Objects::Constructor(_) => "constructor",
+            Objects::Array(_) => "array",
+            Objects::Tuple(_) => "tuple",
+            Objects::Hash(_) => "hash",
+            Objects::Class(_) => "class",
+            Objects::StructType(_) => "struct type",
+            Objects::Instance(_) => "instance",
+            Objects::Variant(_) => "variant",
+            Objects::Struct(_) => "struct",
+            Objects::Error(_) => "error",
+            Objects::Result(_) => "result",
+            Objects::Module(_) => "module",
+            Objects::Nil => "nil",
+        }
+    }
 }
 
-impl BuiltinFunction {
-    pub fn new(name: String) -> Self {
-        BuiltinFunction { name }
+/// Equality is structural for values, containers, enum variants and structs,
+/// by identity for functions, classes and instances, and always false between
+/// different kinds of objects.
+impl PartialEq for Objects {
+    fn eq(&self, other: &Self) -> bool {
+        self.equals(other, &mut Vec::new())
     }
-    pub fn call<'a>(&mut self, args: Vec<Objects>) -> Result<Objects, &'a str> {
-        match &self.name[..] {
-            "len" => {
-                if args.len() != 1 {
-                    return Err("wrong number of arguments for len function");
-                }
-                match &args[0] {
-                    Objects::String(s) => return Ok(Objects::Integer(s.len() as i32)),
-                    Objects::Array(s) => return Ok(Objects::Integer(s.len() as i32)),
-                    _ => return Err("unsupported argument for len"),
-                }
+}
+
+/// Structs being walked further up, so a struct that holds itself is
+/// compared or shown once instead of recursing forever.
+pub type SeenStructs = Vec<*const RefCell<Struct>>;
+
+impl Objects {
+    /// Structural equality. `comparing` holds the left structs already being
+    /// compared further up; meeting one again closes a cycle, which counts as
+    /// equal when the right side closes it at the same point.
+    fn equals(
+        &self,
+        other: &Objects,
+        comparing: &mut Vec<(*const RefCell<Struct>, *const RefCell<Struct>)>,
+    ) -> bool {
+        let all_equal = |s: &[Objects], t: &[Objects], comparing: &mut Vec<_>| {
+            s.len() == t.len() && s.iter().zip(t).all(|(a, b)| a.equals(b, comparing))
+        };
+        match (self, other) {
+            (Objects::Integer(s), Objects::Integer(t)) => s == t,
+            (Objects::Float(s), Objects::Float(t)) => s == t,
+            // numbers compare by value, so 1 == 1.0 inside containers too
+            (Objects::Integer(s), Objects::Float(t)) => *s as f64 == *t as f64,
+            (Objects::Float(s), Objects::Integer(t)) => *s as f64 == *t as f64,
+            (Objects::String(s), Objects::String(t)) => s == t,
+            (Objects::Boolean(s), Objects::Boolean(t)) => s == t,
+            (Objects::Function(s), Objects::Function(t)) => Rc::ptr_eq(&s.body, &t.body),
+            (Objects::BuiltIn(s), Objects::BuiltIn(t)) => s.name == t.name,
+            (Objects::Array(s), Objects::Array(t)) => all_equal(s, t, comparing),
+            (Objects::Tuple(s), Objects::Tuple(t)) => all_equal(s, t, comparing),
+            (Objects::Hash(s), Objects::Hash(t)) => {
+                s.len() == t.len()
+                    && s.iter().all(|(key, value)| match t.get(key) {
+                        Some(other) => value.equals(other, comparing),
+                        None => false,
+                    })
             }
-            "first" => {
-                if args.len() != 1 {
-                    return Err("wrong number of arguments for first function");
-                }
-                match &args[0] {
-                    Objects::String(s) => {
-                        let chr: Vec<char> = s.chars().collect();
-                        return Ok(Objects::String(String::from(chr[0])));
-                    }
-                    Objects::Array(s) => return Ok(s[0].clone()),
-                    _ => return Err("unsupported argument for first"),
-                }
+            (Objects::Class(s), Objects::Class(t)) => Rc::ptr_eq(s, t),
+            (Objects::Instance(s), Objects::Instance(t)) => Rc::ptr_eq(s, t),
+            (Objects::Constructor(s), Objects::Constructor(t)) => Rc::ptr_eq(s, t),
+            (Objects::Variant(s), Objects::Variant(t)) => {
+                Rc::ptr_eq(&s.kind, &t.kind) && all_equal(&s.values, &t.values, comparing)
             }
-            "last" => {
-                if args.len() != 1 {
-                    return Err("wrong number of arguments for last function");
+            (Objects::StructType(s), Objects::StructType(t)) => Rc::ptr_eq(s, t),
+            (Objects::Error(s), Objects::Error(t)) => s == t,
+            (Objects::Result(Ok(s)), Objects::Result(Ok(t)))
+            | (Objects::Result(Err(s)), Objects::Result(Err(t))) => s.equals(t, comparing),
+            (Objects::Module(s), Objects::Module(t)) => Rc::ptr_eq(s, t),
+            (Objects::Struct(s), Objects::Struct(t)) => {
+                if Rc::ptr_eq(s, t) {
+                    return true;
                 }
-                match &args[0] {
-                    Objects::String(s) => {
-                        let chr: Vec<char> = s.chars().collect();
-                        let len = chr.len() - 1;
-                        return Ok(Objects::String(String::from(chr[len])));
-                    }
-                    Objects::Array(s) => {
-                        let len = s.len() - 1;
-                        return Ok(s[len].clone());
-                    }
-                    _ => return Err("unsupported argument for last"),
+                let pair = (Rc::as_ptr(s), Rc::as_ptr(t));
+                if let Some((_, right)) = comparing.iter().find(|(left, _)| *left == pair.0) {
+                    return *right == pair.1;
                 }
-            }
-            "push" => {
-                if args.len() != 2 {
-                    return Err("wrong number of arguments for push function");
-                }
-                match &args[0] {
-                    Objects::Array(s) => {
-                        let mut f: Vec<Objects> = s.clone();
-                        match &args[1] {
-                            Objects::Integer(t) => f.push(Objects::Integer(*t)),
-                            Objects::String(t) => f.push(Objects::String(t.clone())),
-                            Objects::Boolean(t) => f.push(Objects::Boolean(t.clone())),
-                            Objects::Array(t) => f.push(Objects::Array(t.clone())),
-                            _ => {
-                                return Err(
-                                    "push function only supports objects in the second argument",
-                                )
-                            }
-                        }
-                        return Ok(Objects::Array(f));
-                    }
-                    _ => return Err("push function only supports arrays for the first argument"),
+                let (s, t) = (s.borrow(), t.borrow());
+                if Rc::ptr_eq(&s.kind, &t.kind) == false {
+                    return false;
                 }
+                comparing.push(pair);
+                let equal = all_equal(&s.values, &t.values, comparing);
+                comparing.pop();
+                equal
             }
-            "print" => {
-                if args.len() != 1 {
-                    return Err("wrong number of arguments for print function");
-                }
-                match &args[0] {
-                    Objects::String(s) => {
-                        println!("{}", s);
-                        return Ok(Objects::String(String::from("")));
-                    }
-                    Objects::Integer(s) => {
-                        println!("{}", s);
-                        return Ok(Objects::String(String::from("")));
-                    }
-                    Objects::Float(s) => {
-                        println!("{}", s);
-                        return Ok(Objects::String(String::from("")));
-                    }
-                    Objects::Boolean(s) => {
-                        println!("{}", s);
-                        return Ok(Objects::String(String::from("")));
-                    }
-                    Objects::Array(s) => {
-                        for i in s {
-                            println!("{}", i);
-                        }
-                        return Ok(Objects::String(String::from("")));
-                    }
-                    _ => return Err("unsupported argument for print"),
-                }
+            (Objects::Nil, Objects::Nil) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Objects {}
+
+impl Hash for Objects {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // a whole float equals the integer of the same value, so it must
+        // hash like it; this also covers 0.0 and -0.0
+        if let Objects::Float(s) = self {
+            if s.fract() == 0.0 && *s >= i32::MIN as f32 && *s < i32::MAX as f32 {
+                return Objects::Integer(*s as i32).hash(state);
             }
-            _ => return Err("builtin function not found"),
         }
+        mem::discriminant(self).hash(state);
+        match self {
+            Objects::Integer(s) => s.hash(state),
+            Objects::Float(s) => s.to_bits().hash(state),
+            Objects::String(s) => s.hash(state),
+            Objects::Boolean(s) => s.hash(state),
+            Objects::Function(s) => Rc::as_ptr(&s.body).hash(state),
+            Objects::BuiltIn(s) => s.name.hash(state),
+            Objects::Array(s) => s.hash(state),
+            Objects::Tuple(s) => s.hash(state),
+            Objects::Hash(s) => s.len().hash(state),
+            Objects::Class(s) => Rc::as_ptr(s).hash(state),
+            Objects::Instance(s) => Rc::as_ptr(s).hash(state),
+            Objects::Constructor(s) => Rc::as_ptr(s).hash(state),
+            Objects::Variant(s) => {
+                Rc::as_ptr(&s.kind).hash(state);
+                s.values.hash(state);
+            }
+            Objects::StructType(s) => Rc::as_ptr(s).hash(state),
+            Objects::Error(s) => {
+                s.kind.hash(state);
+                s.message.hash(state);
+            }
+            Objects::Result(s) => s.hash(state),
+            Objects::Module(s) => Rc::as_ptr(s).hash(state),
+            // only the kind: field values can change and can hold the struct
+            // itself, and equal structs always share a kind
+            Objects::Struct(s) => Rc::as_ptr(&s.borrow().kind).hash(state),
+            Objects::Nil => {}
+        }
+    }
+}
+
+/// An error raised while evaluating. `kind` groups errors for scripts that
+/// catch them, `trace` lists the functions the error unwound through,
+/// innermost first, and `thrown` holds the value of a `throw`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub line: Option<usize>,
+    pub kind: String,
+    pub trace: Vec<String>,
+    pub thrown: Option<Box<Objects>>,
+    pub returned: Option<Box<Objects>>,
+}
+
+impl RuntimeError {
+    pub fn new(message: &str) -> Self {
+        RuntimeError {
+            message: message.to_string(),
+            line: None,
+            kind: "RuntimeError".to_string(),
+            trace: Vec::new(),
+            thrown: None,
+            returned: None,
+        }
+    }
+
+    /// Carries the value of a `return`, or the `err` result of a `?`, out of
+    /// nested blocks and expressions to the enclosing function, which returns
+    /// it. It is never caught by `catch`.
+    pub fn early_return(value: Objects) -> Self {
+        RuntimeError {
+            returned: Some(Box::new(value)),
+            ..RuntimeError::new("return used outside of a function")
+        }
+    }
+
+    pub fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = kind.to_string();
+        self
+    }
+
+    /// Records that the error unwound through the function `name`.
+    pub fn with_frame(mut self, name: &str) -> Self {
+        self.trace.push(name.to_string());
+        self
+    }
+
+    /// Builds the error raised by `throw value`. Throwing a caught error
+    /// raises it again with its original kind, line and trace.
+    pub fn thrown(value: Objects) -> Self {
+        match &value {
+            Objects::Error(s) => RuntimeError {
+                message: s.message.clone(),
+                line: s.line,
+                kind: s.kind.clone(),
+                trace: s.trace.clone(),
+                thrown: Some(Box::new(value.clone())),
+                returned: None,
+            },
+            Objects::String(s) => RuntimeError {
+                thrown: Some(Box::new(value.clone())),
+                ..RuntimeError::new(s)
+            },
+            _ => RuntimeError {
+                thrown: Some(Box::new(value.clone())),
+                ..RuntimeError::new(&format!("uncaught {}", builtins::to_text(&value)))
+            },
+        }
+    }
+
+    /// The value a `catch` clause binds: the thrown value, or an error
+    /// object describing the runtime error. A thrown error object is rebuilt
+    /// with the line and trace gathered while it unwound.
+    pub fn to_object(&self) -> Objects {
+        match self.thrown.as_deref() {
+            Some(Objects::Error(_)) | None => {
+                return Objects::Error(Rc::new(ErrorValue {
+                    kind: self.kind.clone(),
+                    message: self.message.clone(),
+                    line: self.line,
+                    trace: self.trace.clone(),
+                }))
+            }
+            Some(s) => return s.clone(),
+        }
+    }
+
+    /// Attaches the source line of the failing expression, keeping the line of
+    /// an inner expression if one was already recorded.
+    pub fn with_line(mut self, line: usize) -> Self {
+        if self.line.is_none() {
+            self.line = Some(line);
+        }
+        self
+    }
+}
+
+/// The noun for `count` arguments in arity errors.
+pub fn arguments(count: usize) -> &'static str {
+    match count {
+        1 => "argument",
+        _ => "arguments",
     }
 }
 
+impl From<&str> for RuntimeError {
+    fn from(message: &str) -> Self {
+        RuntimeError::new(message)
+    }
+}
+
+/// An imported file, holding the values it exported.
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub members: HashMap<String, Objects>,
+}
+
+/// An error as a script value, with the fields of the `RuntimeError` it
+/// was caught from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorValue {
+    pub kind: String,
+    pub message: String,
+    pub line: Option<usize>,
+    pub trace: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Function {
-    pub parameters: Vec<String>,
-    pub body: Box<Vec<parser::Statement>>,
-    pub environment: Environment,
+    pub name: String,
+    pub parameters: Vec<parser::Parameter>,
+    pub body: Rc<Vec<parser::Statement>>,
+    pub environment: Rc<RefCell<Environment>>,
 }
 
 impl Function {
-    pub fn new(parameters: Vec<String>, body: Box<Vec<parser::Statement>>) -> Self {
-        let environment = Environment::new();
+    pub fn new(
+        name: String,
+        parameters: Vec<parser::Parameter>,
+        body: Vec<parser::Statement>,
+        environment: Rc<RefCell<Environment>>,
+    ) -> Self {
         Function {
+            name,
             parameters,
-            body,
+            body: Rc::new(body),
             environment,
         }
     }
 }
 
+/// A class declaration: its superclass, its own fields with their default
+/// values, in declaration order, and its own methods.
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub fields: Vec<(String, Objects)>,
+    pub methods: HashMap<String, Function>,
+}
+
+impl Class {
+    /// Finds a method in this class or the nearest superclass defining it,
+    /// along with the class it was found in.
+    pub fn find_method(self: &Rc<Self>, name: &str) -> Option<(Rc<Class>, Function)> {
+        match self.methods.get(name) {
+            Some(s) => return Some((self.clone(), s.clone())),
+            None => match &self.superclass {
+                Some(s) => return s.find_method(name),
+                None => return None,
+            },
+        }
+    }
+
+    /// Checks whether this class is `other` or inherits from it.
+    pub fn is_subclass_of(self: &Rc<Self>, other: &Rc<Class>) -> bool {
+        if Rc::ptr_eq(self, other) {
+            return true;
+        }
+        match &self.superclass {
+            Some(s) => return s.is_subclass_of(other),
+            None => return false,
+        }
+    }
+}
+
+/// An object created by calling a class. Instances are shared, so changing a
+/// field is seen through every reference to the instance.
+#[derive(Debug)]
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: HashMap<String, Objects>,
+}
+
+/// One variant of an enum declaration, with the names of its payloads.
+#[derive(Debug)]
+pub struct VariantType {
+    pub enum_name: String,
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+/// A value built by an enum variant, holding one payload per field.
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub kind: Rc<VariantType>,
+    pub values: Vec<Objects>,
+}
+
+/// A struct declaration, naming its fields in declaration order.
+#[derive(Debug)]
+pub struct StructType {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+/// A struct value, holding one value per field of its declaration. Structs
+/// are shared like instances, but compare by their field values.
+#[derive(Debug)]
+pub struct Struct {
+    pub kind: Rc<StructType>,
+    pub values: Vec<Objects>,
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    value: Objects,
+    mutable: bool,
+}
+
+/// A scope of bindings. Lookups and assignments that miss in this scope
+/// continue in the enclosing one.
 #[derive(Default, Debug, Clone)]
 pub struct Environment {
-    value: HashMap<String, Objects>,
+    value: HashMap<String, Binding>,
+    outer: Option<Rc<RefCell<Environment>>>,
 }
 
 impl Environment {
@@ -152,21 +452,88 @@ impl Environment {
         Self::default()
     }
 
+    pub fn new_enclosed(outer: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            value: HashMap::new(),
+            outer: Some(outer),
+        }
+    }
+
+    /// Adds a mutable binding, replacing any binding of the same name in
+    /// this scope.
     pub fn add(&mut self, name: String, value: Objects) {
-        self.value.insert(name, value);
+        self.value.insert(
+            name,
+            Binding {
+                value,
+                mutable: true,
+            },
+        );
+    }
+
+    /// Declares a new binding in this scope. Declaring a name twice in the
+    /// same scope is an error; shadowing a name from an outer scope is not.
+    pub fn declare(
+        &mut self,
+        name: String,
+        value: Objects,
+        mutable: bool,
+    ) -> Result<(), RuntimeError> {
+        if self.value.contains_key(&name) {
+            return Err(RuntimeError::new(&format!(
+                "identifier {} is already declared in this scope",
+                name
+            )));
+        }
+        self.value.insert(name, Binding { value, mutable });
+        return Ok(());
     }
 
-    pub fn remove(&mut self, name: String) {
-        self.value.remove_entry(&name);
+    /// Changes the value of an existing binding in the nearest scope that
+    /// declares it.
+    pub fn assign(&mut self, name: String, value: Objects) -> Result<(), RuntimeError> {
+        match self.value.get_mut(&name) {
+            Some(s) if s.mutable => {
+                s.value = value;
+                return Ok(());
+            }
+            Some(_s) => {
+                return Err(RuntimeError::new(&format!(
+                    "cannot assign to constant {}",
+                    name
+                )))
+            }
+            None => match &self.outer {
+                Some(s) => return s.borrow_mut().assign(name, value),
+                None => {
+                    let message = format!("identifier {} not found", name);
+                    return Err(RuntimeError::new(&message).with_kind("NameError"));
+                }
+            },
+        }
     }
 
-    pub fn search(&mut self, name: String) -> Option<&Objects> {
-        return self.value.get(&name);
+    pub fn search(&self, name: String) -> Option<Objects> {
+        match self.value.get(&name) {
+            Some(s) => return Some(s.value.clone()),
+            None => match &self.outer {
+                Some(s) => return s.borrow().search(name),
+                None => return None,
+            },
+        }
     }
 }
 
 impl fmt::Display for Objects {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_seen(f, &mut Vec::new())
+    }
+}
+
+impl Objects {
+    /// Writes the value, showing a struct met again inside itself as
+    /// `<cycle>`.
+    fn fmt_seen(&self, f: &mut fmt::Formatter, seen: &mut SeenStructs) -> fmt::Result {
         match &self {
             Objects::Integer(i) => write!(f, "Integer: {}", i),
             Objects::Float(i) => write!(f, "Float: {}", i),
@@ -174,18 +541,88 @@ impl fmt::Display for Objects {
             Objects::Boolean(b) => write!(f, "Boolean: {}", b),
             Objects::Function(_s) => write!(f, "Function"),
             Objects::BuiltIn(_s) => write!(f, "Builtin Function"),
+            Objects::Class(s) => write!(f, "Class {}", s.name),
+            Objects::Instance(s) => write!(f, "Instance of {}", s.borrow().class.name),
+            Objects::Constructor(s) => write!(f, "Constructor {}::{}", s.enum_name, s.name),
+            Objects::Variant(s) => {
+                write!(f, "Variant: {}::{}", s.kind.enum_name, s.kind.name)?;
+                if s.values.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                for (i, value) in s.values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt_seen(f, seen)?;
+                }
+                return write!(f, ")");
+            }
+            Objects::StructType(s) => write!(f, "Struct {}", s.name),
+            Objects::Struct(s) if seen.contains(&Rc::as_ptr(s)) => write!(f, "<cycle>"),
+            Objects::Struct(s) => {
+                seen.push(Rc::as_ptr(s));
+                let s = s.borrow();
+                write!(f, "{} {{", s.kind.name)?;
+                for (i, (name, value)) in s.kind.fields.iter().zip(&s.values).enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, " {}: ", name)?;
+                    value.fmt_seen(f, seen)?;
+                }
+                seen.pop();
+                return write!(f, " }}");
+            }
+            Objects::Error(s) => write!(f, "{}: {}", s.kind, s.message),
+            Objects::Module(s) => write!(f, "Module {}", s.name),
+            Objects::Result(Ok(s)) => {
+                write!(f, "Ok(")?;
+                s.fmt_seen(f, seen)?;
+                return write!(f, ")");
+            }
+            Objects::Result(Err(s)) => {
+                write!(f, "Err(")?;
+                s.fmt_seen(f, seen)?;
+                return write!(f, ")");
+            }
+            Objects::Nil => write!(f, "Nil"),
             Objects::Array(s) => {
                 for i in s {
-                    write!(f, "Array object {0} ", i)?;
+                    write!(f, "Array object ")?;
+                    i.fmt_seen(f, seen)?;
+                    write!(f, " ")?;
                 }
                 return Ok(());
             }
+            Objects::Tuple(s) => {
+                write!(f, "Tuple (")?;
+                for (i, value) in s.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt_seen(f, seen)?;
+                }
+                return write!(f, ")");
+            }
             Objects::Hash(s) => {
                 for i in s {
-                    write!(f, "Hash key: {0}, value: {1}", i.0, i.1)?;
+                    write!(f, "Hash key: ")?;
+                    i.0.fmt_seen(f, seen)?;
+                    write!(f, ", value: ")?;
+                    i.1.fmt_seen(f, seen)?;
                 }
                 return Ok(());
-            },
+            }
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {0}: {1}", line, self.message),
+            None => write!(f, "{}", self.message),
         }
     }
 }
@@ -193,7 +630,7 @@ impl fmt::Display for Objects {
 impl fmt::Display for Environment {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for (i, j) in &self.value {
-            write!(f, "{0}: {1}", i, j)?;
+            write!(f, "{0}: {1}", i, j.value)?;
         }
         return Ok(());
     }
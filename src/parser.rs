@@ -4,6 +4,7 @@ use tokens::TokenTypes;
 
 pub struct Parser {
     token_vector: Vec<TokenTypes>,
+    line_vector: Vec<usize>,
     current_token: usize,
     next_token: usize,
 }
@@ -20,10 +21,31 @@ pub enum Statement {
         value: Box<Expression>,
     },
 
+    DestructureStatement {
+        pattern: Pattern,
+        value: Box<Expression>,
+        mutable: bool,
+    },
+
     ReturnStatement {
         value: Box<Expression>,
     },
 
+    ThrowStatement {
+        value: Box<Expression>,
+        line: usize,
+    },
+
+    ImportStatement {
+        path: String,
+        alias: String,
+        line: usize,
+    },
+
+    ExportStatement {
+        statement: Box<Statement>,
+    },
+
     ExpressionStatement {
         value: Box<Expression>,
     },
@@ -38,9 +60,29 @@ impl fmt::Display for Statement {
             Statement::ConstStatement { name, value } => {
                 write!(f, "Const Statement name: {0}, value: {1}", name, *value)
             }
+            Statement::DestructureStatement {
+                pattern,
+                value,
+                mutable: _,
+            } => write!(
+                f,
+                "Destructure Statement pattern: {0}, value: {1}",
+                pattern, *value
+            ),
             Statement::ReturnStatement { value } => {
                 write!(f, "Return Statement value: {0}", *value)
             }
+            Statement::ThrowStatement { value, line: _ } => {
+                write!(f, "Throw Statement value: {0}", *value)
+            }
+            Statement::ImportStatement {
+                path,
+                alias,
+                line: _,
+            } => write!(f, "Import Statement path: {0}, alias: {1}", path, alias),
+            Statement::ExportStatement { statement } => {
+                write!(f, "Export Statement: {0}", *statement)
+            }
             Statement::ExpressionStatement { value } => {
                 write!(f, "Expression Statement value: {0}", *value)
             }
@@ -66,9 +108,14 @@ pub enum Expression {
         elements: Vec<Expression>,
     },
 
+    TupleLit {
+        elements: Vec<Expression>,
+    },
+
     IndexExpression {
         left: Box<Expression>,
         right: Box<Expression>,
+        line: usize,
     },
 
     HashMap {
@@ -78,12 +125,15 @@ pub enum Expression {
 
     IdentifierLit {
         name: String,
+        line: usize,
     },
 
     BoolExp {
         value: bool,
     },
 
+    NilLit,
+
     IfExpr {
         condition: Box<Expression>,
         then: Box<Vec<Statement>>,
@@ -97,7 +147,7 @@ pub enum Expression {
 
     FunctionExpr {
         identifier: String,
-        parameters: Vec<String>,
+        parameters: Vec<Parameter>,
         body: Box<Vec<Statement>>,
     },
 
@@ -105,27 +155,160 @@ pub enum Expression {
         left: Box<Expression>,
         operator: tokens::TokenTypes,
         right: Box<Expression>,
+        line: usize,
     },
 
     CallExpr {
         identifier: String,
         parameters: Vec<Expression>,
+        line: usize,
     },
 
     Prefix {
         operator: tokens::TokenTypes,
         right: Box<Expression>,
+        line: usize,
     },
 
     VarChange {
         identifier: String,
         right: Box<Expression>,
+        line: usize,
     },
 
     CompoundOperation {
         identifier: String,
         operator: tokens::TokenTypes,
         right: Box<Expression>,
+        line: usize,
+    },
+
+    MatchExpr {
+        value: Box<Expression>,
+        arms: Vec<MatchArm>,
+        line: usize,
+    },
+
+    ClassExpr {
+        identifier: String,
+        superclass: Option<String>,
+        fields: Vec<String>,
+        initializers: Vec<Expression>,
+        methods: Vec<Expression>,
+    },
+
+    MemberExpr {
+        object: Box<Expression>,
+        member: String,
+        line: usize,
+    },
+
+    MethodCall {
+        object: Box<Expression>,
+        method: String,
+        parameters: Vec<Expression>,
+        line: usize,
+    },
+
+    MemberChange {
+        object: Box<Expression>,
+        member: String,
+        right: Box<Expression>,
+        line: usize,
+    },
+
+    SuperCall {
+        method: String,
+        parameters: Vec<Expression>,
+        line: usize,
+    },
+
+    EnumExpr {
+        identifier: String,
+        variants: Vec<(String, Vec<String>)>,
+    },
+
+    StructExpr {
+        identifier: String,
+        fields: Vec<String>,
+    },
+
+    StructLit {
+        identifier: String,
+        fields: Vec<String>,
+        values: Vec<Expression>,
+        line: usize,
+    },
+
+    NamedArgument {
+        name: String,
+        value: Box<Expression>,
+    },
+
+    Spread {
+        value: Box<Expression>,
+    },
+
+    PropagateExpr {
+        value: Box<Expression>,
+        line: usize,
+    },
+
+    TryExpr {
+        body: Box<Vec<Statement>>,
+        catch_name: Option<String>,
+        catch_body: Option<Box<Vec<Statement>>>,
+        finally: Option<Box<Vec<Statement>>>,
+    },
+}
+
+/// A function parameter: `a`, `a = default` or `...rest`. Defaults are
+/// evaluated on each call that leaves the parameter out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub default: Option<Expression>,
+    pub rest: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// A literal value, matched with `==`.
+    Literal { value: Expression },
+
+    /// `_`, matches anything without binding it.
+    Wildcard,
+
+    /// A name, matches anything and binds it to the name inside the arm.
+    Binding { name: String },
+
+    /// `[p1, p2]`, matches arrays of exactly that length element by element.
+    Array { elements: Vec<Pattern> },
+
+    /// `(p1, p2)`, matches tuples of exactly that length element by element.
+    Tuple { elements: Vec<Pattern> },
+
+    /// `{"k": p}`, matches hashes holding every listed key; other keys are ignored.
+    Hash {
+        keys: Vec<Expression>,
+        values: Vec<Pattern>,
+    },
+
+    /// `p1 | p2`, matches when any alternative matches.
+    Or { alternatives: Vec<Pattern> },
+
+    /// `Name(p1, p2)`, matches values built by the enum variant `Name`,
+    /// payload by payload.
+    Variant {
+        name: String,
+        elements: Vec<Pattern>,
     },
 }
 
@@ -141,7 +324,13 @@ impl fmt::Display for Expression {
                 }
                 return Ok(());
             }
-            Expression::IndexExpression { left, right } => {
+            Expression::TupleLit { elements } => {
+                for i in elements {
+                    write!(f, "Tuple Element: {0}", i)?;
+                }
+                return Ok(());
+            }
+            Expression::IndexExpression { left, right, .. } => {
                 write!(f, "Index Expression: left: {0}, right: {1}", left, right)
             }
             Expression::HashMap { keys, values } => {
@@ -151,18 +340,18 @@ impl fmt::Display for Expression {
                 }
                 return Ok(());
             }
-            Expression::IdentifierLit { name } => write!(f, "Identifier Literal: {0}", name),
+            Expression::IdentifierLit { name, .. } => write!(f, "Identifier Literal: {0}", name),
             Expression::BoolExp { value } => write!(f, "Boolean Expression: {0}", value),
+            Expression::NilLit => write!(f, "Nil Literal"),
             Expression::IfExpr {
                 condition,
                 then: _,
                 other: _,
             } => write!(f, "If Expression: condition: {0}", condition),
 
-            Expression::WhileExpr {
-                condition,
-                body: _,
-            } => write!(f, "While Expression: condition: {}", condition),
+            Expression::WhileExpr { condition, body: _ } => {
+                write!(f, "While Expression: condition: {}", condition)
+            }
             Expression::FunctionExpr {
                 identifier,
                 parameters: _,
@@ -172,6 +361,7 @@ impl fmt::Display for Expression {
                 left,
                 operator,
                 right,
+                line: _,
             } => write!(
                 f,
                 "Infix Operation: left: {0}, operator: {1}, right: {2}",
@@ -180,6 +370,7 @@ impl fmt::Display for Expression {
             Expression::CallExpr {
                 identifier,
                 parameters,
+                line: _,
             } => {
                 write!(f, "Call Expression identifier: {0}", identifier)?;
                 for i in parameters {
@@ -187,7 +378,11 @@ impl fmt::Display for Expression {
                 }
                 return Ok(());
             }
-            Expression::Prefix { operator, right } => write!(
+            Expression::Prefix {
+                operator,
+                right,
+                line: _,
+            } => write!(
                 f,
                 "Infix Operation: operator: {0}, right: {1}",
                 operator, *right
@@ -196,26 +391,182 @@ impl fmt::Display for Expression {
                 identifier,
                 operator,
                 right,
+                line: _,
             } => write!(
                 f,
                 "Compound Operation: identifier: {0}, operator: {1}, right: {2}",
                 identifier, operator, *right
             ),
-            Expression::VarChange { identifier, right } => write!(
+            Expression::VarChange {
+                identifier,
+                right,
+                line: _,
+            } => write!(
                 f,
                 "Change Variable: identifier: {0}, right: {1}",
                 identifier, *right
             ),
+            Expression::MatchExpr {
+                value,
+                arms: _,
+                line: _,
+            } => write!(f, "Match Expression: value: {0}", value),
+            Expression::ClassExpr {
+                identifier,
+                superclass: _,
+                fields: _,
+                initializers: _,
+                methods: _,
+            } => write!(f, "Class Expression: identifier: {0}", identifier),
+            Expression::MemberExpr {
+                object,
+                member,
+                line: _,
+            } => write!(
+                f,
+                "Member Expression: object: {0}, member: {1}",
+                object, member
+            ),
+            Expression::MethodCall {
+                object,
+                method,
+                parameters,
+                line: _,
+            } => {
+                write!(f, "Method Call: object: {0}, method: {1}", object, method)?;
+                for i in parameters {
+                    write!(f, "Method Call Parameters: {0}", i)?;
+                }
+                return Ok(());
+            }
+            Expression::MemberChange {
+                object,
+                member,
+                right,
+                line: _,
+            } => write!(
+                f,
+                "Change Member: object: {0}, member: {1}, right: {2}",
+                object, member, right
+            ),
+            Expression::EnumExpr {
+                identifier,
+                variants,
+            } => {
+                write!(f, "Enum Expression: identifier: {0}", identifier)?;
+                for (i, _j) in variants {
+                    write!(f, "Enum Variant: {0}", i)?;
+                }
+                return Ok(());
+            }
+            Expression::StructExpr { identifier, fields } => {
+                write!(f, "Struct Expression: identifier: {0}", identifier)?;
+                for i in fields {
+                    write!(f, "Struct Field: {0}", i)?;
+                }
+                return Ok(());
+            }
+            Expression::StructLit {
+                identifier,
+                fields,
+                values,
+                line: _,
+            } => {
+                write!(f, "Struct Literal: identifier: {0}", identifier)?;
+                for (i, j) in fields.iter().zip(values) {
+                    write!(f, "Struct Literal Field: {0}, value: {1}", i, j)?;
+                }
+                return Ok(());
+            }
+            Expression::NamedArgument { name, value } => {
+                write!(f, "Named Argument: name: {0}, value: {1}", name, value)
+            }
+            Expression::Spread { value } => write!(f, "Spread: {0}", value),
+            Expression::PropagateExpr { value, line: _ } => {
+                write!(f, "Propagate Expression: value: {0}", value)
+            }
+            Expression::TryExpr {
+                body,
+                catch_name,
+                catch_body: _,
+                finally: _,
+            } => {
+                for i in body.iter() {
+                    write!(f, "Try Statement: {0}", i)?;
+                }
+                if let Some(s) = catch_name {
+                    write!(f, "Catch Name: {0}", s)?;
+                }
+                return Ok(());
+            }
+            Expression::SuperCall {
+                method,
+                parameters,
+                line: _,
+            } => {
+                write!(f, "Super Call: method: {0}", method)?;
+                for i in parameters {
+                    write!(f, "Super Call Parameters: {0}", i)?;
+                }
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Literal { value } => write!(f, "Literal Pattern: {0}", value),
+            Pattern::Wildcard => write!(f, "Wildcard Pattern"),
+            Pattern::Binding { name } => write!(f, "Binding Pattern: {0}", name),
+            Pattern::Array { elements } => {
+                for i in elements {
+                    write!(f, "Array Pattern Element: {0}", i)?;
+                }
+                return Ok(());
+            }
+            Pattern::Tuple { elements } => {
+                for i in elements {
+                    write!(f, "Tuple Pattern Element: {0}", i)?;
+                }
+                return Ok(());
+            }
+            Pattern::Hash { keys, values } => {
+                for (i, j) in keys.iter().zip(values) {
+                    write!(f, "Hash Pattern Key: {0}", i)?;
+                    write!(f, "Hash Pattern Value: {0}", j)?;
+                }
+                return Ok(());
+            }
+            Pattern::Or { alternatives } => {
+                for i in alternatives {
+                    write!(f, "Or Pattern Alternative: {0}", i)?;
+                }
+                return Ok(());
+            }
+            Pattern::Variant { name, elements } => {
+                write!(f, "Variant Pattern: {0}", name)?;
+                for i in elements {
+                    write!(f, "Variant Pattern Element: {0}", i)?;
+                }
+                return Ok(());
+            }
         }
     }
 }
 
 impl Eq for Expression {}
 
+impl Eq for Pattern {}
+
+impl Eq for Parameter {}
+
 impl Parser {
-    pub fn new(line: Vec<tokens::TokenTypes>) -> Self {
+    pub fn new(line: Vec<tokens::TokenTypes>, line_numbers: Vec<usize>) -> Self {
         Parser {
             token_vector: line,
+            line_vector: line_numbers,
             current_token: 0,
             next_token: 1,
         }
@@ -230,6 +581,9 @@ impl Parser {
                 Ok(s) => final_vector.push(s),
                 Err(e) => return Err(e),
             }
+            while self.token_vector[self.next_token] == tokens::TokenTypes::Semicolon {
+                self.advance_tokens();
+            }
             self.advance_tokens();
             if &self.token_vector[self.current_token] == &tokens::TokenTypes::EndOfLine {
                 break;
@@ -261,6 +615,24 @@ impl Parser {
                     Err(e) => return Err(e),
                 }
             }
+            tokens::TokenTypes::Keywords(tokens::Keywords::Import) => {
+                return self.parse_import();
+            }
+            tokens::TokenTypes::Keywords(tokens::Keywords::Export) => {
+                self.advance_tokens();
+                let statement = self.check_statement()?;
+                return Ok(Statement::ExportStatement {
+                    statement: Box::new(statement),
+                });
+            }
+            tokens::TokenTypes::Keywords(tokens::Keywords::Throw) => {
+                let line = self.current_line();
+                let result_op: Expression = self.parse_loop_expressions()?;
+                return Ok(Statement::ThrowStatement {
+                    value: Box::from(result_op),
+                    line,
+                });
+            }
 
             _ => {
                 let expression_statement = self.parse_expressions();
@@ -287,6 +659,26 @@ impl Parser {
                 }
             }
 
+            tokens::TokenTypes::Keywords(tokens::Keywords::Match) => {
+                return self.parse_match();
+            }
+
+            tokens::TokenTypes::Keywords(tokens::Keywords::Class) => {
+                return self.parse_class();
+            }
+
+            tokens::TokenTypes::Keywords(tokens::Keywords::Enum) => {
+                return self.parse_enum();
+            }
+
+            tokens::TokenTypes::Keywords(tokens::Keywords::Struct) => {
+                return self.parse_struct();
+            }
+
+            tokens::TokenTypes::Keywords(tokens::Keywords::Try) => {
+                return self.parse_try();
+            }
+
             tokens::TokenTypes::Keywords(tokens::Keywords::While) => {
                 let while_expression = self.parse_while();
                 match while_expression {
@@ -313,50 +705,94 @@ impl Parser {
                 Ok(false_expression)
             }
 
-            tokens::TokenTypes::Identifier(s) => {
+            tokens::TokenTypes::Identifier(s)
+                if self.token_vector[self.next_token] == tokens::TokenTypes::Operator('=') =>
+            {
                 let name = s.clone();
-                match self.token_vector[self.next_token] {
-                    tokens::TokenTypes::Delim('[') => {
-                        self.advance_tokens();
-                        self.advance_tokens();
-                        let right = self.expression_parser(&tokens::TokenTypes::Delim(']'));
-                        match right {
-                            Ok(t) => {
-                                return Ok(Expression::IndexExpression {
-                                    left: Box::new(Expression::IdentifierLit { name }),
-                                    right: Box::new(t),
-                                })
-                            }
-                            Err(e) => return Err(e),
-                        }
-                    }
-                    tokens::TokenTypes::Operator('(') => {
-                        return self.parse_call();
-                    }
-                    tokens::TokenTypes::CompoundOperator(t) => {
-                        self.advance_tokens();
-                        let rt = self.parse_loop_expressions()?;
-                        let compound_op = Expression::CompoundOperation {
-                            identifier: name.clone(),
-                            operator: tokens::TokenTypes::CompoundOperator(t),
-                            right: Box::from(rt),
-                        };
-                        return Ok(Expression::VarChange {
-                            identifier: name.clone(),
-                            right: Box::from(compound_op),
-                        });
-                    }
-                    _ => return Err("wrong identifier statement"),
-                }
+                self.advance_tokens();
+                let line = self.current_line();
+                let rt = self.parse_loop_expressions()?;
+                return Ok(Expression::VarChange {
+                    identifier: name,
+                    right: Box::from(rt),
+                    line,
+                });
+            }
+
+            tokens::TokenTypes::Identifier(s)
+                if matches!(
+                    self.token_vector[self.next_token],
+                    tokens::TokenTypes::CompoundOperator(_)
+                ) =>
+            {
+                let name = s.clone();
+                let operator = self.token_vector[self.next_token].clone();
+                self.advance_tokens();
+                let line = self.current_line();
+                let rt = self.parse_loop_expressions()?;
+                let compound_op = Expression::CompoundOperation {
+                    identifier: name.clone(),
+                    operator,
+                    right: Box::from(rt),
+                    line,
+                };
+                return Ok(Expression::VarChange {
+                    identifier: name,
+                    right: Box::from(compound_op),
+                    line,
+                });
             }
             //tokens::TokenTypes::Comment => {
             //    println!("This is a comment: Line ignored");
             //}
             _ => {
-                let expression = self.expression_parser(&tokens::TokenTypes::Semicolon);
-                match expression {
-                    Ok(s) => return Ok(s),
-                    Err(e) => return Err(e),
+                let left_op = self.parse_prefix_expressions()?;
+                self.advance_tokens();
+                let expression = self.infix_expression_parser(0, left_op)?;
+                if let Expression::MemberExpr {
+                    object,
+                    member,
+                    line,
+                } = &expression
+                {
+                    match self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Operator('=') => {
+                            let rt = self.parse_loop_expressions()?;
+                            return Ok(Expression::MemberChange {
+                                object: object.clone(),
+                                member: member.clone(),
+                                right: Box::new(rt),
+                                line: *line,
+                            });
+                        }
+                        tokens::TokenTypes::CompoundOperator(t) => {
+                            let rt = self.parse_loop_expressions()?;
+                            let compound_op = Expression::InfixOp {
+                                left: Box::new(expression.clone()),
+                                operator: tokens::TokenTypes::Operator(t),
+                                right: Box::new(rt),
+                                line: *line,
+                            };
+                            return Ok(Expression::MemberChange {
+                                object: object.clone(),
+                                member: member.clone(),
+                                right: Box::new(compound_op),
+                                line: *line,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+                match self.token_vector[self.current_token] {
+                    tokens::TokenTypes::Semicolon => return Ok(expression),
+                    // the last expression of a block may leave out its semicolon,
+                    // the closing brace is left for the block to consume
+                    tokens::TokenTypes::Delim('}') => {
+                        self.current_token -= 1;
+                        self.next_token -= 1;
+                        return Ok(expression);
+                    }
+                    _ => return Err("expected a ; (semicolon) at the end of the expression"),
                 }
             }
         }
@@ -370,6 +806,9 @@ impl Parser {
     fn parse_variable<'a>(&mut self) -> Result<Statement, &'a str> {
         let identifier: String;
         self.advance_tokens();
+        if self.is_destructure_start() {
+            return self.parse_destructure(true);
+        }
         match &self.token_vector[self.current_token] {
             tokens::TokenTypes::Identifier(s) => identifier = s.clone(),
             _ => {
@@ -394,16 +833,19 @@ impl Parser {
     fn parse_constant<'a>(&mut self) -> Result<Statement, &'a str> {
         let identifier: String;
         self.advance_tokens();
-        match &self.token_vector[self.next_token] {
-            tokens::TokenTypes::Operator('=') => identifier = "=".to_string(),
-            tokens::TokenTypes::EndOfLine => {
-                return Err("error, expected = sign");
-            }
+        if self.is_destructure_start() {
+            return self.parse_destructure(false);
+        }
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Identifier(s) => identifier = s.clone(),
             _ => {
                 return Err("error, expected an identifier");
             }
         }
         self.advance_tokens();
+        if self.match_current_operator('=') == false {
+            return Err("error, expected = sign, constant must be initialized");
+        }
         if &self.token_vector.len() <= &self.next_token {
             return Err("expected an expression (parser.rs, line 272)");
         }
@@ -415,125 +857,477 @@ impl Parser {
         })
     }
 
-    fn parse_return<'a>(&mut self) -> Result<Statement, &'a str> {
-        if &self.token_vector.len() <= &self.next_token {
-            return Err("expected an expression (parser.rs, line 311)");
+    fn is_destructure_start(&self) -> bool {
+        match self.token_vector[self.current_token] {
+            tokens::TokenTypes::Operator('(')
+            | tokens::TokenTypes::Delim('[')
+            | tokens::TokenTypes::Delim('{') => true,
+            _ => false,
         }
-        let result_op: Expression = self.parse_loop_expressions()?;
-        Ok(Statement::ReturnStatement {
-            value: Box::from(result_op),
-        })
     }
 
-    fn parse_if<'a>(&mut self) -> Result<Expression, &'a str> {
-        self.advance_tokens();
-        if self.match_current_operator('(') == false {
-            return Err("Error, expected (");
-        }
-        self.advance_tokens();
-
-        let condition_result = self.expression_parser(&tokens::TokenTypes::Operator(')'));
-        let condition;
-        match condition_result {
-            Ok(v) => condition = Box::new(v),
-            Err(e) => return Err(e),
-        }
-
-        if self.match_current_operator(')') == false {
-            return Err("Error, expected )");
-        }
-
+    /// Parses `var (a, b) = ...`, `var [a, b] = ...` or `var {a, b} = ...`
+    /// starting at the opening bracket of the target.
+    fn parse_destructure<'a>(&mut self, mutable: bool) -> Result<Statement, &'a str> {
+        let pattern = self.parse_destructure_target()?;
         self.advance_tokens();
-
-        if self.match_current_delim('{') == false {
-            return Err("Error, expected {");
+        if self.match_current_operator('=') == false {
+            return Err("expected a = sign, destructured names must be initialized");
         }
+        let result_op: Expression = self.parse_loop_expressions()?;
+        Ok(Statement::DestructureStatement {
+            pattern,
+            value: Box::from(result_op),
+            mutable,
+        })
+    }
 
-        self.advance_tokens();
+    fn parse_destructure_target<'a>(&mut self) -> Result<Pattern, &'a str> {
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Identifier(s) if s == "_" => return Ok(Pattern::Wildcard),
+            tokens::TokenTypes::Identifier(s) => return Ok(Pattern::Binding { name: s.clone() }),
+            tokens::TokenTypes::Operator('(') | tokens::TokenTypes::Delim('[') => {
+                let closing = match self.token_vector[self.current_token] {
+                    tokens::TokenTypes::Operator('(') => tokens::TokenTypes::Operator(')'),
+                    _ => tokens::TokenTypes::Delim(']'),
+                };
+                let mut elements: Vec<Pattern> = Vec::new();
+                while self.token_vector[self.current_token] != closing {
+                    self.advance_tokens();
+                    if self.token_vector[self.current_token] == closing {
+                        break;
+                    }
+                    elements.push(self.parse_destructure_target()?);
+                    self.advance_tokens();
+                    if self.token_vector[self.current_token] != tokens::TokenTypes::Comma
+                        && self.token_vector[self.current_token] != closing
+                    {
+                        return Err("expected a , (comma) or closing bracket in destructuring");
+                    }
+                }
+                if closing == tokens::TokenTypes::Operator(')') {
+                    return Ok(Pattern::Tuple { elements });
+                }
+                return Ok(Pattern::Array { elements });
+            }
+            tokens::TokenTypes::Delim('{') => {
+                // `{name, age}` takes the values stored under the keys
+                // "name" and "age"
+                let mut keys: Vec<Expression> = Vec::new();
+                let mut values: Vec<Pattern> = Vec::new();
+                while self.match_current_delim('}') == false {
+                    self.advance_tokens();
+                    match &self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Identifier(s) => {
+                            keys.push(Expression::StringLit { string: s.clone() });
+                            values.push(Pattern::Binding { name: s.clone() });
+                        }
+                        tokens::TokenTypes::Delim('}') => break,
+                        _ => return Err("expected a name in hash destructuring"),
+                    }
+                    self.advance_tokens();
+                    match self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Comma | tokens::TokenTypes::Delim('}') => {}
+                        _ => return Err("expected a , (comma) or } in hash destructuring"),
+                    }
+                }
+                return Ok(Pattern::Hash { keys, values });
+            }
+            _ => return Err("expected a name or a destructuring pattern"),
+        }
+    }
 
-        let consequence = Box::new(self.parse_statement(&tokens::TokenTypes::Delim('}'))?);
+    /// Parses `import "path" as name;`. Without `as` the module is bound to
+    /// the file name without its extension.
+    fn parse_import<'a>(&mut self) -> Result<Statement, &'a str> {
+        let line = self.current_line();
+        self.advance_tokens();
+        let path;
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Strings(s) => path = s.clone(),
+            _ => return Err("expected a module path string after import"),
+        }
+        self.advance_tokens();
+        let alias;
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Keywords(tokens::Keywords::As) => {
+                self.advance_tokens();
+                match &self.token_vector[self.current_token] {
+                    tokens::TokenTypes::Identifier(s) => alias = s.clone(),
+                    _ => return Err("expected a module name after as"),
+                }
+                self.advance_tokens();
+            }
+            _ => {
+                let file_name = path.rsplit('/').next().unwrap_or(&path);
+                alias = file_name.trim_end_matches(".axel").to_string();
+            }
+        }
+        if self.token_vector[self.current_token] != tokens::TokenTypes::Semicolon {
+            return Err("expected a ; (semicolon) at the end of the import");
+        }
+        Ok(Statement::ImportStatement { path, alias, line })
+    }
 
+    fn parse_return<'a>(&mut self) -> Result<Statement, &'a str> {
+        if &self.token_vector.len() <= &self.next_token {
+            return Err("expected an expression (parser.rs, line 311)");
+        }
+        if &self.token_vector[self.next_token] == &tokens::TokenTypes::Semicolon {
+            self.advance_tokens();
+            return Ok(Statement::ReturnStatement {
+                value: Box::from(Expression::NilLit),
+            });
+        }
+        let result_op: Expression = self.parse_loop_expressions()?;
+        Ok(Statement::ReturnStatement {
+            value: Box::from(result_op),
+        })
+    }
+
+    fn parse_if<'a>(&mut self) -> Result<Expression, &'a str> {
         self.advance_tokens();
-        if self.match_current_delim('}') == false {
-            return Err("Error, expected }");
+
+        // the condition runs up to the opening brace, parentheses around it
+        // are optional and just group the expression
+        let condition_result = self.expression_parser(&tokens::TokenTypes::Delim('{'));
+        let condition;
+        match condition_result {
+            Ok(v) => condition = Box::new(v),
+            Err(e) => return Err(e),
         }
 
+        let consequence = Box::new(self.parse_block()?);
+
         let then: Option<Box<Vec<Statement>>>;
         match self.token_vector[self.next_token] {
             tokens::TokenTypes::Keywords(tokens::Keywords::Else) => {
                 self.advance_tokens();
                 self.advance_tokens();
-                self.advance_tokens();
-                let then_box = Box::new(self.parse_statement(&tokens::TokenTypes::Delim('}'))?);
-                self.advance_tokens();
-                if self.match_current_delim('}') == false {
-                    return Err("Error, expected }");
+                // `else if` chains nest: the else branch holds the next if
+                if self.token_vector[self.current_token]
+                    == tokens::TokenTypes::Keywords(tokens::Keywords::If)
+                {
+                    let else_if = self.parse_if()?;
+                    then = Some(Box::new(vec![Statement::ExpressionStatement {
+                        value: Box::new(else_if),
+                    }]));
+                } else {
+                    let then_box = Box::new(self.parse_block()?);
+                    then = Some(then_box);
                 }
-                then = Some(then_box);
             }
             _ => {
                 then = None;
             }
         }
         Ok(Expression::IfExpr {
-            condition: condition,
+            condition,
             then: consequence,
             other: then,
         })
     }
 
-    fn parse_while<'a>(&mut self) -> Result<Expression, &'a str> {
+    fn parse_match<'a>(&mut self) -> Result<Expression, &'a str> {
+        let line = self.current_line();
         self.advance_tokens();
-        if self.match_current_operator('(') == false {
-            return Err("Error, expected (");
-        }
+        let value = Box::new(self.expression_parser(&tokens::TokenTypes::Delim('{'))?);
+
+        let mut arms: Vec<MatchArm> = Vec::new();
         self.advance_tokens();
+        while self.match_current_delim('}') == false {
+            let pattern = self.parse_pattern()?;
+            self.advance_tokens();
 
-        let condition_result = self.expression_parser(&tokens::TokenTypes::Operator(')'));
-        let condition;
-        match condition_result {
-            Ok(v) => condition = Box::new(v),
-            Err(e) => return Err(e),
-        }
+            let mut guard = None;
+            if self.token_vector[self.current_token]
+                == tokens::TokenTypes::Keywords(tokens::Keywords::If)
+            {
+                self.advance_tokens();
+                guard = Some(self.expression_parser(&tokens::TokenTypes::FatArrow)?);
+            }
+            if self.token_vector[self.current_token] != tokens::TokenTypes::FatArrow {
+                return Err("expected => after a match pattern");
+            }
+            self.advance_tokens();
+
+            // an arm body is either a block, which needs no trailing comma,
+            // or a single expression
+            let body;
+            let block_body = self.match_current_delim('{');
+            if block_body {
+                body = self.parse_block()?;
+                self.advance_tokens();
+            } else {
+                let left_op = self.parse_prefix_expressions()?;
+                self.advance_tokens();
+                let value = self.infix_expression_parser(0, left_op)?;
+                body = vec![Statement::ExpressionStatement {
+                    value: Box::new(value),
+                }];
+            }
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
 
-        if self.match_current_operator(')') == false {
-            return Err("Error, expected )");
+            match self.token_vector[self.current_token] {
+                tokens::TokenTypes::Comma => self.advance_tokens(),
+                tokens::TokenTypes::Delim('}') => break,
+                _ if block_body => continue,
+                _ => return Err("expected a , (comma) or } (right brace) after a match arm"),
+            }
         }
 
-        self.advance_tokens();
+        Ok(Expression::MatchExpr { value, arms, line })
+    }
 
-        if self.match_current_delim('{') == false {
-            return Err("Error, expected {");
+    fn parse_pattern<'a>(&mut self) -> Result<Pattern, &'a str> {
+        let mut alternatives = vec![self.parse_single_pattern()?];
+        while self.token_vector[self.next_token] == tokens::TokenTypes::Pipe {
+            self.advance_tokens();
+            self.advance_tokens();
+            alternatives.push(self.parse_single_pattern()?);
+        }
+        if alternatives.len() == 1 {
+            return Ok(alternatives.remove(0));
         }
+        Ok(Pattern::Or { alternatives })
+    }
 
+    fn parse_single_pattern<'a>(&mut self) -> Result<Pattern, &'a str> {
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Identifier(s) if s == "_" => return Ok(Pattern::Wildcard),
+            tokens::TokenTypes::Identifier(s)
+                if self.token_vector[self.next_token] == tokens::TokenTypes::Operator('(') =>
+            {
+                let name = s.clone();
+                let mut elements: Vec<Pattern> = Vec::new();
+                self.advance_tokens();
+                if self.token_vector[self.next_token] == tokens::TokenTypes::Operator(')') {
+                    self.advance_tokens();
+                    return Ok(Pattern::Variant { name, elements });
+                }
+                while self.match_current_operator(')') == false {
+                    self.advance_tokens();
+                    elements.push(self.parse_pattern()?);
+                    self.advance_tokens();
+                    match self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Comma | tokens::TokenTypes::Operator(')') => {}
+                        _ => return Err("expected a , (comma) or ) in variant pattern"),
+                    }
+                }
+                return Ok(Pattern::Variant { name, elements });
+            }
+            tokens::TokenTypes::Identifier(s) => return Ok(Pattern::Binding { name: s.clone() }),
+            tokens::TokenTypes::Delim('[') => {
+                let mut elements: Vec<Pattern> = Vec::new();
+                if self.token_vector[self.next_token] == tokens::TokenTypes::Delim(']') {
+                    self.advance_tokens();
+                    return Ok(Pattern::Array { elements });
+                }
+                while self.match_current_delim(']') == false {
+                    self.advance_tokens();
+                    elements.push(self.parse_pattern()?);
+                    self.advance_tokens();
+                    match self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Comma | tokens::TokenTypes::Delim(']') => {}
+                        _ => return Err("expected a , (comma) or ] in array pattern"),
+                    }
+                }
+                return Ok(Pattern::Array { elements });
+            }
+            tokens::TokenTypes::Operator('(') => {
+                let mut elements: Vec<Pattern> = Vec::new();
+                if self.token_vector[self.next_token] == tokens::TokenTypes::Operator(')') {
+                    self.advance_tokens();
+                    return Ok(Pattern::Tuple { elements });
+                }
+                while self.match_current_operator(')') == false {
+                    self.advance_tokens();
+                    elements.push(self.parse_pattern()?);
+                    self.advance_tokens();
+                    match self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Comma | tokens::TokenTypes::Operator(')') => {}
+                        _ => return Err("expected a , (comma) or ) in tuple pattern"),
+                    }
+                }
+                return Ok(Pattern::Tuple { elements });
+            }
+            tokens::TokenTypes::Delim('{') => {
+                let mut keys: Vec<Expression> = Vec::new();
+                let mut values: Vec<Pattern> = Vec::new();
+                if self.token_vector[self.next_token] == tokens::TokenTypes::Delim('}') {
+                    self.advance_tokens();
+                    return Ok(Pattern::Hash { keys, values });
+                }
+                while self.match_current_delim('}') == false {
+                    self.advance_tokens();
+                    keys.push(self.parse_prefix_expressions()?);
+                    self.advance_tokens();
+                    match self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Colon => self.advance_tokens(),
+                        _ => return Err("expected a : (colon) in hash pattern"),
+                    }
+                    values.push(self.parse_pattern()?);
+                    self.advance_tokens();
+                    match self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Comma | tokens::TokenTypes::Delim('}') => {}
+                        _ => return Err("expected a , (comma) or } in hash pattern"),
+                    }
+                }
+                return Ok(Pattern::Hash { keys, values });
+            }
+            tokens::TokenTypes::NumbersInt(_)
+            | tokens::TokenTypes::NumbersFloat(_)
+            | tokens::TokenTypes::Strings(_)
+            | tokens::TokenTypes::Operator('-')
+            | tokens::TokenTypes::Keywords(tokens::Keywords::True)
+            | tokens::TokenTypes::Keywords(tokens::Keywords::False)
+            | tokens::TokenTypes::Keywords(tokens::Keywords::Nil) => {
+                let value = self.parse_prefix_expressions()?;
+                return Ok(Pattern::Literal { value });
+            }
+            _ => return Err("expected a pattern"),
+        }
+    }
+
+    /// Parses `try { } catch (e) { } finally { }`, where either clause may
+    /// be left out but not both, and the parentheses and the name in `catch`
+    /// are optional.
+    fn parse_try<'a>(&mut self) -> Result<Expression, &'a str> {
         self.advance_tokens();
+        let body = Box::new(self.parse_block()?);
 
-        let body = Box::new(self.parse_statement(&tokens::TokenTypes::Delim('}'))?);
+        let mut catch_name = None;
+        let mut catch_body = None;
+        if self.token_vector[self.next_token]
+            == tokens::TokenTypes::Keywords(tokens::Keywords::Catch)
+        {
+            self.advance_tokens();
+            self.advance_tokens();
+            let parenthesized = self.match_current_operator('(');
+            if parenthesized {
+                self.advance_tokens();
+            }
+            if let tokens::TokenTypes::Identifier(s) = &self.token_vector[self.current_token] {
+                catch_name = Some(s.clone());
+                self.advance_tokens();
+            }
+            if parenthesized {
+                if self.match_current_operator(')') == false {
+                    return Err("expected a ) after the catch name");
+                }
+                self.advance_tokens();
+            }
+            catch_body = Some(Box::new(self.parse_block()?));
+        }
 
-        self.advance_tokens();
-        if self.match_current_delim('}') == false {
-            return Err("Error, expected }");
+        let mut finally = None;
+        if self.token_vector[self.next_token]
+            == tokens::TokenTypes::Keywords(tokens::Keywords::Finally)
+        {
+            self.advance_tokens();
+            self.advance_tokens();
+            finally = Some(Box::new(self.parse_block()?));
         }
 
-        Ok(Expression::WhileExpr {
-            condition,
+        if catch_body.is_none() && finally.is_none() {
+            return Err("expected a catch or finally clause after try");
+        }
+        Ok(Expression::TryExpr {
             body,
+            catch_name,
+            catch_body,
+            finally,
         })
     }
 
+    fn parse_while<'a>(&mut self) -> Result<Expression, &'a str> {
+        self.advance_tokens();
+
+        // the condition runs up to the opening brace, parentheses around it
+        // are optional and just group the expression
+        let condition_result = self.expression_parser(&tokens::TokenTypes::Delim('{'));
+        let condition;
+        match condition_result {
+            Ok(v) => condition = Box::new(v),
+            Err(e) => return Err(e),
+        }
+
+        let body = Box::new(self.parse_block()?);
+
+        Ok(Expression::WhileExpr { condition, body })
+    }
+
+    /// Parses a prefix expression followed by any postfix `.member`,
+    /// `.method(...)` or `[index]` accesses, which bind tighter than any
+    /// infix operator.
     fn parse_prefix_expressions<'a>(&mut self) -> Result<Expression, &'a str> {
-        match &self.token_vector[self.current_token] {
-            tokens::TokenTypes::NumbersInt(s) => {
-                return Ok(Expression::NumberLit { number: s.clone() })
-            }
-            tokens::TokenTypes::NumbersFloat(s) => {
-                return Ok(Expression::FloatLit { number: s.clone() })
+        let mut left_op = self.parse_primary_expressions()?;
+        loop {
+            match self.token_vector[self.next_token] {
+                tokens::TokenTypes::Dot => {
+                    self.advance_tokens();
+                    let line = self.current_line();
+                    self.advance_tokens();
+                    let member;
+                    match &self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Identifier(s) => member = s.clone(),
+                        _ => return Err("expected a member name after ."),
+                    }
+                    if self.token_vector[self.next_token] == tokens::TokenTypes::Operator('(') {
+                        self.advance_tokens();
+                        let parameters =
+                            self.parse_comma_separation(&tokens::TokenTypes::Operator(')'))?;
+                        left_op = Expression::MethodCall {
+                            object: Box::new(left_op),
+                            method: member,
+                            parameters,
+                            line,
+                        };
+                    } else {
+                        left_op = Expression::MemberExpr {
+                            object: Box::new(left_op),
+                            member,
+                            line,
+                        };
+                    }
+                }
+                tokens::TokenTypes::Delim('[') => {
+                    let line = self.current_line();
+                    self.advance_tokens();
+                    self.advance_tokens();
+                    let right = self.expression_parser(&tokens::TokenTypes::Delim(']'))?;
+                    left_op = Expression::IndexExpression {
+                        left: Box::new(left_op),
+                        right: Box::new(right),
+                        line,
+                    };
+                }
+                tokens::TokenTypes::Question if self.is_postfix_question() => {
+                    self.advance_tokens();
+                    left_op = Expression::PropagateExpr {
+                        value: Box::new(left_op),
+                        line: self.current_line(),
+                    };
+                }
+                _ => return Ok(left_op),
             }
+        }
+    }
+
+    fn parse_primary_expressions<'a>(&mut self) -> Result<Expression, &'a str> {
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::NumbersInt(s) => return Ok(Expression::NumberLit { number: *s }),
+            tokens::TokenTypes::NumbersFloat(s) => return Ok(Expression::FloatLit { number: *s }),
             tokens::TokenTypes::Strings(s) => {
                 return Ok(Expression::StringLit { string: s.clone() })
             }
             tokens::TokenTypes::Identifier(s) => {
                 let name = s.clone();
+                let line = self.current_line();
 
                 match self.token_vector[self.next_token] {
                     tokens::TokenTypes::Delim('[') => {
@@ -543,8 +1337,9 @@ impl Parser {
                         match right {
                             Ok(t) => {
                                 return Ok(Expression::IndexExpression {
-                                    left: Box::new(Expression::IdentifierLit { name }),
+                                    left: Box::new(Expression::IdentifierLit { name, line }),
                                     right: Box::new(t),
+                                    line,
                                 })
                             }
                             Err(e) => return Err(e),
@@ -553,12 +1348,66 @@ impl Parser {
                     tokens::TokenTypes::Operator('(') => {
                         return self.parse_call();
                     }
-                    _ => return Ok(Expression::IdentifierLit { name }),
+                    // `Name { field: ...` is a struct literal; checking for the
+                    // colon keeps `if x { y }` a condition followed by a block
+                    tokens::TokenTypes::Delim('{') if self.is_struct_literal() => {
+                        return self.parse_struct_literal(name);
+                    }
+                    _ => return Ok(Expression::IdentifierLit { name, line }),
                 }
             }
             tokens::TokenTypes::Operator('(') => {
+                // `(a)` only groups, `()`, `(a,)` and `(a, b)` are tuples
+                let mut elements: Vec<Expression> = Vec::new();
+                if self.token_vector[self.next_token] == tokens::TokenTypes::Operator(')') {
+                    self.advance_tokens();
+                    return Ok(Expression::TupleLit { elements });
+                }
                 self.advance_tokens();
-                return self.expression_parser(&tokens::TokenTypes::Operator(')'));
+                let first = self.parse_prefix_expressions()?;
+                self.advance_tokens();
+                let first = self.infix_expression_parser(0, first)?;
+                if self.match_current_operator(')') {
+                    return Ok(first);
+                }
+                elements.push(first);
+                while self.match_current_operator(')') == false {
+                    if self.token_vector[self.current_token] != tokens::TokenTypes::Comma {
+                        return Err("expected a , (comma) or ) in tuple");
+                    }
+                    if self.token_vector[self.next_token] == tokens::TokenTypes::Operator(')') {
+                        self.advance_tokens();
+                        break;
+                    }
+                    self.advance_tokens();
+                    let element = self.parse_prefix_expressions()?;
+                    self.advance_tokens();
+                    elements.push(self.infix_expression_parser(0, element)?);
+                }
+                return Ok(Expression::TupleLit { elements });
+            }
+            tokens::TokenTypes::Keywords(tokens::Keywords::Super) => {
+                let line = self.current_line();
+                self.advance_tokens();
+                if self.token_vector[self.current_token] != tokens::TokenTypes::Dot {
+                    return Err("expected a . (dot) after super");
+                }
+                self.advance_tokens();
+                let method;
+                match &self.token_vector[self.current_token] {
+                    tokens::TokenTypes::Identifier(s) => method = s.clone(),
+                    _ => return Err("expected a method name after super."),
+                }
+                self.advance_tokens();
+                if self.match_current_operator('(') == false {
+                    return Err("expected a method call after super.");
+                }
+                let parameters = self.parse_comma_separation(&tokens::TokenTypes::Operator(')'))?;
+                return Ok(Expression::SuperCall {
+                    method,
+                    parameters,
+                    line,
+                });
             }
             tokens::TokenTypes::Delim('[') => {
                 let elements: Vec<Expression> =
@@ -587,6 +1436,7 @@ impl Parser {
                 return Ok(Expression::HashMap { keys, values });
             }
             tokens::TokenTypes::Operator('-') => {
+                let line = self.current_line();
                 self.advance_tokens();
                 let parse_exp = self.parse_prefix_expressions();
                 let expression;
@@ -597,9 +1447,11 @@ impl Parser {
                 return Ok(Expression::Prefix {
                     operator: tokens::TokenTypes::Operator('-'),
                     right: Box::new(expression),
+                    line,
                 });
             }
             tokens::TokenTypes::Bang => {
+                let line = self.current_line();
                 self.advance_tokens();
                 let parse_exp = self.parse_prefix_expressions();
                 let expression;
@@ -610,6 +1462,7 @@ impl Parser {
                 return Ok(Expression::Prefix {
                     operator: tokens::TokenTypes::Bang,
                     right: Box::new(expression),
+                    line,
                 });
             }
             tokens::TokenTypes::Keywords(tokens::Keywords::True) => {
@@ -618,6 +1471,10 @@ impl Parser {
             tokens::TokenTypes::Keywords(tokens::Keywords::False) => {
                 return Ok(Expression::BoolExp { value: false })
             }
+            tokens::TokenTypes::Keywords(tokens::Keywords::Nil) => return Ok(Expression::NilLit),
+            tokens::TokenTypes::Keywords(tokens::Keywords::If) => return self.parse_if(),
+            tokens::TokenTypes::Keywords(tokens::Keywords::Match) => return self.parse_match(),
+            tokens::TokenTypes::Keywords(tokens::Keywords::Try) => return self.parse_try(),
             _ => return Err("expected an expression (parser.rs, line 480)"),
         }
     }
@@ -635,17 +1492,58 @@ impl Parser {
         if self.match_current_operator('(') == false {
             return Err("Error, expected (");
         }
+        let parameters = self.parse_function_parameters()?;
+
         self.advance_tokens();
 
-        let mut parameters: Vec<String> = Vec::new();
+        let statement = Box::new(self.parse_block()?);
+
+        Ok(Expression::FunctionExpr {
+            identifier,
+            parameters,
+            body: statement,
+        })
+    }
+
+    /// Parses a parenthesized list of parameters, starting at `(` and ending
+    /// at `)`. A `...rest` parameter has to come last.
+    fn parse_function_parameters<'a>(&mut self) -> Result<Vec<Parameter>, &'a str> {
+        self.advance_tokens();
+
+        let mut parameters: Vec<Parameter> = Vec::new();
 
         while self.match_current_operator(')') == false {
+            if parameters.last().map(|p| p.rest) == Some(true) {
+                return Err("a rest parameter must be the last parameter");
+            }
+            let mut rest = false;
+            if self.token_vector[self.current_token] == tokens::TokenTypes::Ellipsis {
+                rest = true;
+                self.advance_tokens();
+            }
+            let name;
             match &self.token_vector[self.current_token] {
-                tokens::TokenTypes::Identifier(s) => parameters.push(s.clone()),
+                tokens::TokenTypes::Identifier(s) => name = s.clone(),
                 _ => return Err("expected an identifier"),
             }
             self.advance_tokens();
 
+            let mut default = None;
+            if self.match_current_operator('=') {
+                if rest {
+                    return Err("a rest parameter can't have a default value");
+                }
+                self.advance_tokens();
+                let left_op = self.parse_prefix_expressions()?;
+                self.advance_tokens();
+                default = Some(self.infix_expression_parser(0, left_op)?);
+            }
+            parameters.push(Parameter {
+                name,
+                default,
+                rest,
+            });
+
             if self.match_current_operator(')') == false {
                 match &self.token_vector[self.current_token] {
                     tokens::TokenTypes::Comma => self.advance_tokens(),
@@ -653,32 +1551,274 @@ impl Parser {
                 }
             }
         }
+        return Ok(parameters);
+    }
 
+    fn parse_class<'a>(&mut self) -> Result<Expression, &'a str> {
+        let identifier: String;
+        self.advance_tokens();
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Identifier(s) => identifier = s.clone(),
+            _ => return Err("Error, expected a class name"),
+        }
         self.advance_tokens();
+        let mut superclass: Option<String> = None;
+        if self.token_vector[self.current_token] == tokens::TokenTypes::Colon {
+            self.advance_tokens();
+            match &self.token_vector[self.current_token] {
+                tokens::TokenTypes::Identifier(s) => superclass = Some(s.clone()),
+                _ => return Err("Error, expected a superclass name after :"),
+            }
+            self.advance_tokens();
+        }
+        if self.match_current_delim('{') == false {
+            return Err("Error, expected {");
+        }
+
+        let mut fields: Vec<String> = Vec::new();
+        let mut initializers: Vec<Expression> = Vec::new();
+        let mut methods: Vec<Expression> = Vec::new();
+        loop {
+            self.advance_tokens();
+            match &self.token_vector[self.current_token] {
+                tokens::TokenTypes::Keywords(tokens::Keywords::Var) => {
+                    self.advance_tokens();
+                    match &self.token_vector[self.current_token] {
+                        tokens::TokenTypes::Identifier(s) => fields.push(s.clone()),
+                        _ => return Err("expected a field name"),
+                    }
+                    // fields without an initializer start out as nil
+                    match self.token_vector[self.next_token] {
+                        tokens::TokenTypes::Semicolon => {
+                            self.advance_tokens();
+                            initializers.push(Expression::NilLit);
+                        }
+                        tokens::TokenTypes::Operator('=') => {
+                            self.advance_tokens();
+                            initializers.push(self.parse_loop_expressions()?);
+                        }
+                        _ => return Err("expected a ; (semicolon) or = after a field name"),
+                    }
+                }
+                tokens::TokenTypes::Keywords(tokens::Keywords::Function) => {
+                    methods.push(self.parse_function()?);
+                }
+                tokens::TokenTypes::Delim('}') => break,
+                _ => return Err("expected a field or method declaration in class body"),
+            }
+        }
 
+        Ok(Expression::ClassExpr {
+            identifier,
+            superclass,
+            fields,
+            initializers,
+            methods,
+        })
+    }
+
+    fn parse_enum<'a>(&mut self) -> Result<Expression, &'a str> {
+        let identifier: String;
+        self.advance_tokens();
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Identifier(s) => identifier = s.clone(),
+            _ => return Err("Error, expected an enum name"),
+        }
+        self.advance_tokens();
         if self.match_current_delim('{') == false {
             return Err("Error, expected {");
         }
 
+        let mut variants: Vec<(String, Vec<String>)> = Vec::new();
+        while self.match_current_delim('}') == false {
+            self.advance_tokens();
+            let name;
+            match &self.token_vector[self.current_token] {
+                tokens::TokenTypes::Identifier(s) => name = s.clone(),
+                tokens::TokenTypes::Delim('}') => break,
+                _ => return Err("expected a variant name in enum body"),
+            }
+            let mut fields: Vec<String> = Vec::new();
+            self.advance_tokens();
+            if self.match_current_operator('(') {
+                for parameter in self.parse_function_parameters()? {
+                    if parameter.default.is_some() || parameter.rest {
+                        return Err("enum variant fields can't have defaults or rest");
+                    }
+                    fields.push(parameter.name);
+                }
+                self.advance_tokens();
+            }
+            match self.token_vector[self.current_token] {
+                tokens::TokenTypes::Comma | tokens::TokenTypes::Delim('}') => {}
+                _ => return Err("expected a , (comma) or } after an enum variant"),
+            }
+            variants.push((name, fields));
+        }
+
+        Ok(Expression::EnumExpr {
+            identifier,
+            variants,
+        })
+    }
+
+    fn parse_struct<'a>(&mut self) -> Result<Expression, &'a str> {
+        let identifier: String;
         self.advance_tokens();
+        match &self.token_vector[self.current_token] {
+            tokens::TokenTypes::Identifier(s) => identifier = s.clone(),
+            _ => return Err("Error, expected a struct name"),
+        }
+        self.advance_tokens();
+        if self.match_current_delim('{') == false {
+            return Err("Error, expected {");
+        }
+
+        let mut fields: Vec<String> = Vec::new();
+        while self.match_current_delim('}') == false {
+            self.advance_tokens();
+            match &self.token_vector[self.current_token] {
+                tokens::TokenTypes::Identifier(s) => fields.push(s.clone()),
+                tokens::TokenTypes::Delim('}') => break,
+                _ => return Err("expected a field name in struct body"),
+            }
+            self.advance_tokens();
+            match self.token_vector[self.current_token] {
+                tokens::TokenTypes::Comma | tokens::TokenTypes::Delim('}') => {}
+                _ => return Err("expected a , (comma) or } after a struct field"),
+            }
+        }
 
-        let statement_result = self.parse_statement(&tokens::TokenTypes::Delim('}'));
-        let statement;
+        Ok(Expression::StructExpr { identifier, fields })
+    }
 
-        match statement_result {
-            Ok(v) => statement = Box::new(v),
-            _ => return Err("error parsing statement"),
+    /// A `?` starts a `cond ? a : b` when its `:` follows at the same nesting
+    /// level before the expression ends, otherwise it is the postfix
+    /// propagation operator.
+    fn is_postfix_question(&self) -> bool {
+        let first = self.next_token + 1;
+        let mut depth = 0;
+        // conditionals opened inside the then branch, each taking a `:`
+        let mut nested = 0;
+        let mut index = first;
+        loop {
+            match self.token_vector.get(index) {
+                None
+                | Some(tokens::TokenTypes::EndOfLine)
+                | Some(tokens::TokenTypes::Semicolon) => return true,
+                Some(tokens::TokenTypes::Operator('('))
+                | Some(tokens::TokenTypes::Delim('['))
+                | Some(tokens::TokenTypes::Delim('{')) => depth += 1,
+                Some(tokens::TokenTypes::Operator(')'))
+                | Some(tokens::TokenTypes::Delim(']'))
+                | Some(tokens::TokenTypes::Delim('}')) => {
+                    if depth == 0 {
+                        return true;
+                    }
+                    depth -= 1;
+                }
+                Some(tokens::TokenTypes::Colon) if depth == 0 => {
+                    // the then branch can't be empty, so `x? : y` is postfix
+                    if index == first {
+                        return true;
+                    }
+                    if nested == 0 {
+                        return false;
+                    }
+                    nested -= 1;
+                }
+                Some(tokens::TokenTypes::Question) if depth == 0 => {
+                    if index == first {
+                        return true;
+                    }
+                    if self.ends_expression(index + 1) == false {
+                        nested += 1;
+                    }
+                }
+                Some(tokens::TokenTypes::Comma)
+                | Some(tokens::TokenTypes::FatArrow)
+                | Some(tokens::TokenTypes::Operator('='))
+                | Some(tokens::TokenTypes::CompoundOperator(_))
+                | Some(tokens::TokenTypes::Keywords(
+                    tokens::Keywords::Var
+                    | tokens::Keywords::Const
+                    | tokens::Keywords::Return
+                    | tokens::Keywords::If
+                    | tokens::Keywords::Else
+                    | tokens::Keywords::While
+                    | tokens::Keywords::Class
+                    | tokens::Keywords::Enum
+                    | tokens::Keywords::Struct
+                    | tokens::Keywords::Throw
+                    | tokens::Keywords::Import
+                    | tokens::Keywords::Export,
+                )) if depth == 0 => return true,
+                _ => {}
+            }
+            index += 1;
         }
+    }
 
+    /// Whether the token at `index` closes the expression before it, so a
+    /// `?` just before it can't start a conditional.
+    fn ends_expression(&self, index: usize) -> bool {
+        match self.token_vector.get(index) {
+            Some(tokens::TokenTypes::Semicolon)
+            | Some(tokens::TokenTypes::Comma)
+            | Some(tokens::TokenTypes::Colon)
+            | Some(tokens::TokenTypes::Question)
+            | Some(tokens::TokenTypes::Operator(')'))
+            | Some(tokens::TokenTypes::Delim(']'))
+            | Some(tokens::TokenTypes::Delim('}'))
+            | Some(tokens::TokenTypes::EndOfLine)
+            | None => true,
+            _ => false,
+        }
+    }
+
+    fn is_struct_literal(&self) -> bool {
+        match (
+            self.token_vector.get(self.next_token + 1),
+            self.token_vector.get(self.next_token + 2),
+        ) {
+            (Some(tokens::TokenTypes::Identifier(_)), Some(tokens::TokenTypes::Colon)) => true,
+            _ => false,
+        }
+    }
+
+    /// Parses `Name { field: value, ... }` starting at the name and leaves the
+    /// current token on the closing `}`.
+    fn parse_struct_literal<'a>(&mut self, identifier: String) -> Result<Expression, &'a str> {
+        let line = self.current_line();
         self.advance_tokens();
-        if self.match_current_delim('}') == false {
-            return Err("Error, expected }");
+        let mut fields: Vec<String> = Vec::new();
+        let mut values: Vec<Expression> = Vec::new();
+        while self.match_current_delim('}') == false {
+            self.advance_tokens();
+            match &self.token_vector[self.current_token] {
+                tokens::TokenTypes::Identifier(s) => fields.push(s.clone()),
+                tokens::TokenTypes::Delim('}') => break,
+                _ => return Err("expected a field name in struct literal"),
+            }
+            self.advance_tokens();
+            if self.token_vector[self.current_token] != tokens::TokenTypes::Colon {
+                return Err("expected a : (colon) after a field name");
+            }
+            self.advance_tokens();
+            let value = self.parse_prefix_expressions()?;
+            self.advance_tokens();
+            values.push(self.infix_expression_parser(0, value)?);
+            match self.token_vector[self.current_token] {
+                tokens::TokenTypes::Comma | tokens::TokenTypes::Delim('}') => {}
+                _ => return Err("expected a , (comma) or } in struct literal"),
+            }
         }
 
-        Ok(Expression::FunctionExpr {
-            identifier: identifier,
-            parameters: parameters,
-            body: statement,
+        Ok(Expression::StructLit {
+            identifier,
+            fields,
+            values,
+            line,
         })
     }
 
@@ -689,6 +1829,7 @@ impl Parser {
     }
 
     fn parse_call<'a>(&mut self) -> Result<Expression, &'a str> {
+        let line = self.current_line();
         let identifier;
         match &self.token_vector[self.current_token] {
             tokens::TokenTypes::Identifier(s) => identifier = s.clone(),
@@ -705,6 +1846,7 @@ impl Parser {
         Ok(Expression::CallExpr {
             identifier,
             parameters,
+            line,
         })
     }
 
@@ -713,8 +1855,32 @@ impl Parser {
         delimiter: &tokens::TokenTypes,
     ) -> Result<Vec<Expression>, &'a str> {
         let mut parameters: Vec<Expression> = Vec::new();
+        if &self.token_vector[self.next_token] == delimiter {
+            self.advance_tokens();
+            return Ok(parameters);
+        }
         while &self.token_vector[self.current_token] != delimiter {
             self.advance_tokens();
+
+            // `...value` spreads an array or tuple, `name: value` names a call
+            // argument
+            let mut spread = false;
+            let mut named = None;
+            match &self.token_vector[self.current_token] {
+                tokens::TokenTypes::Ellipsis => {
+                    spread = true;
+                    self.advance_tokens();
+                }
+                tokens::TokenTypes::Identifier(s)
+                    if self.token_vector[self.next_token] == tokens::TokenTypes::Colon =>
+                {
+                    named = Some(s.clone());
+                    self.advance_tokens();
+                    self.advance_tokens();
+                }
+                _ => {}
+            }
+
             let left_op;
             let parsed_prefix = self.parse_prefix_expressions();
             match parsed_prefix {
@@ -723,14 +1889,22 @@ impl Parser {
             }
 
             self.advance_tokens();
-            if &self.token_vector[self.current_token] != &tokens::TokenTypes::Comma {
-                let result_op = self.infix_expression_parser(0, left_op);
-                match result_op {
-                    Ok(s) => parameters.push(s.clone()),
-                    Err(e) => return Err(e),
-                }
-            } else {
-                parameters.push(left_op);
+            let result_op = self.infix_expression_parser(0, left_op);
+            match result_op {
+                Ok(s) if spread => parameters.push(Expression::Spread { value: Box::new(s) }),
+                Ok(s) => match named {
+                    Some(name) => parameters.push(Expression::NamedArgument {
+                        name,
+                        value: Box::new(s),
+                    }),
+                    None => parameters.push(s),
+                },
+                Err(e) => return Err(e),
+            }
+            match &self.token_vector[self.current_token] {
+                tokens::TokenTypes::Comma => continue,
+                s if s == delimiter => break,
+                _ => return Err("expected a , (comma) between elements"),
             }
         }
         return Ok(parameters);
@@ -748,76 +1922,104 @@ impl Parser {
                 Ok(s) => statement.push(s),
                 Err(e) => return Err(e),
             }
+            // block-ending expressions like `match x { ... };` may be followed
+            // by a semicolon of their own
+            while self.token_vector[self.next_token] == tokens::TokenTypes::Semicolon {
+                self.advance_tokens();
+            }
             if &self.token_vector[self.next_token] == delimiter {
                 break;
             }
+            if &self.token_vector[self.next_token] == &tokens::TokenTypes::EndOfLine {
+                return Err("reached end of line before closing the block");
+            }
             self.advance_tokens();
         }
 
         Ok(statement)
     }
 
+    /// Parses a `{ ... }` block starting on the `{` token and leaves the
+    /// current token on the closing `}`. An empty block yields no statements.
+    fn parse_block<'a>(&mut self) -> Result<Vec<Statement>, &'a str> {
+        if self.match_current_delim('{') == false {
+            return Err("Error, expected {");
+        }
+        if &self.token_vector[self.next_token] == &tokens::TokenTypes::Delim('}') {
+            self.advance_tokens();
+            return Ok(Vec::new());
+        }
+        self.advance_tokens();
+        let statement = self.parse_statement(&tokens::TokenTypes::Delim('}'))?;
+        self.advance_tokens();
+        if self.match_current_delim('}') == false {
+            return Err("Error, expected }");
+        }
+        Ok(statement)
+    }
+
     fn expression_parser<'a>(
         &mut self,
         delimiter: &tokens::TokenTypes,
     ) -> Result<Expression, &'a str> {
-        let mut precedence = 0;
-        let mut left_op;
+        let left_op;
         let op = self.parse_prefix_expressions();
         match op {
             Ok(s) => left_op = s,
             Err(e) => return Err(e),
         }
-        loop {
-            self.advance_tokens();
-            let result_op = self.infix_expression_parser(precedence, left_op);
-            match result_op {
-                Ok(v) => left_op = v,
-                Err(e) => return Err(e),
-            };
-            if &self.token_vector[self.current_token] == delimiter {
-                break;
-            }
-            precedence = Parser::get_precedence(&self.token_vector[self.current_token]);
+        self.advance_tokens();
+        let result_op = self.infix_expression_parser(0, left_op)?;
+        if &self.token_vector[self.current_token] != delimiter {
+            return Err("unexpected token after expression");
         }
 
-        Ok(left_op)
+        Ok(result_op)
     }
 
+    /// Folds every operator binding tighter than `precedence` into `left_op`.
+    /// Starts with the current token on the operator following `left_op` and
+    /// stops on the first token that is not such an operator.
     fn infix_expression_parser<'a>(
         &mut self,
         precedence: usize,
         left_op: Expression,
     ) -> Result<Expression, &'a str> {
-        let op;
-        match self.token_vector[self.current_token] {
-            tokens::TokenTypes::Operator(s) => op = tokens::TokenTypes::Operator(s),
-            tokens::TokenTypes::CompoundOperator(s) => op = tokens::TokenTypes::CompoundOperator(s),
-            tokens::TokenTypes::Compare(tokens::Comparison::Equal) => {
-                op = tokens::TokenTypes::Compare(tokens::Comparison::Equal)
-            }
-            tokens::TokenTypes::Compare(tokens::Comparison::NotEqual) => {
-                op = tokens::TokenTypes::Compare(tokens::Comparison::NotEqual)
-            }
-            tokens::TokenTypes::Compare(tokens::Comparison::Less) => {
-                op = tokens::TokenTypes::Compare(tokens::Comparison::Less)
-            }
-            tokens::TokenTypes::Compare(tokens::Comparison::LessE) => {
-                op = tokens::TokenTypes::Compare(tokens::Comparison::LessE)
-            }
-            tokens::TokenTypes::Compare(tokens::Comparison::Greater) => {
-                op = tokens::TokenTypes::Compare(tokens::Comparison::Greater)
+        let mut left_op = left_op;
+        loop {
+            let op = self.token_vector[self.current_token].clone();
+            let next_precedence: usize = Parser::get_precedence(&op);
+            if next_precedence <= precedence {
+                return Ok(left_op);
             }
-            tokens::TokenTypes::Compare(tokens::Comparison::GreaterE) => {
-                op = tokens::TokenTypes::Compare(tokens::Comparison::GreaterE)
+            let line = self.current_line();
+
+            // `cond ? a : b` is shorthand for an if expression; the else side
+            // is parsed at the lowest precedence so conditionals nest to the right
+            if op == tokens::TokenTypes::Question {
+                self.advance_tokens();
+                let then_op = self.parse_prefix_expressions()?;
+                self.advance_tokens();
+                let then_op = self.infix_expression_parser(0, then_op)?;
+                if self.token_vector[self.current_token] != tokens::TokenTypes::Colon {
+                    return Err("expected a : (colon) in conditional expression");
+                }
+                self.advance_tokens();
+                let other_op = self.parse_prefix_expressions()?;
+                self.advance_tokens();
+                let other_op = self.infix_expression_parser(0, other_op)?;
+                left_op = Expression::IfExpr {
+                    condition: Box::new(left_op),
+                    then: Box::new(vec![Statement::ExpressionStatement {
+                        value: Box::new(then_op),
+                    }]),
+                    other: Some(Box::new(vec![Statement::ExpressionStatement {
+                        value: Box::new(other_op),
+                    }])),
+                };
+                continue;
             }
-            _ => return Ok(left_op),
-        }
 
-        let next_precedence: usize = Parser::get_precedence(&op);
-        if next_precedence > precedence
-            && &self.token_vector[self.next_token] != &tokens::TokenTypes::Semicolon
-        {
             self.advance_tokens();
             let mut right_op;
             let prefix_op = self.parse_prefix_expressions();
@@ -832,27 +2034,39 @@ impl Parser {
                 Ok(v) => right_op = v,
                 Err(e) => return Err(e),
             };
-            return Ok(Expression::InfixOp {
-                left: Box::new(left_op.clone()),
+            left_op = Expression::InfixOp {
+                left: Box::new(left_op),
                 operator: op,
-                right: Box::new(right_op.clone()),
-            });
-        } else {
-            return Ok(left_op);
+                right: Box::new(right_op),
+                line,
+            };
         }
     }
 
     fn get_precedence(token: &tokens::TokenTypes) -> usize {
         match token {
-            tokens::TokenTypes::Operator('+') => 2,
-            tokens::TokenTypes::Operator('-') => 2,
-            tokens::TokenTypes::Operator('*') => 3,
-            tokens::TokenTypes::Operator('/') => 3,
-            tokens::TokenTypes::Compare(_s) => 1,
+            tokens::TokenTypes::Operator('+') => 6,
+            tokens::TokenTypes::Operator('-') => 6,
+            tokens::TokenTypes::Operator('*') => 7,
+            tokens::TokenTypes::Operator('/') => 7,
+            tokens::TokenTypes::Operator('%') => 7,
+            tokens::TokenTypes::Shift(_s) => 5,
+            tokens::TokenTypes::Compare(_s) => 4,
+            tokens::TokenTypes::Keywords(tokens::Keywords::Is) => 4,
+            tokens::TokenTypes::And => 3,
+            tokens::TokenTypes::Or => 2,
+            tokens::TokenTypes::Question => 1,
             _ => 0,
         }
     }
 
+    fn current_line(&self) -> usize {
+        match self.line_vector.get(self.current_token) {
+            Some(s) => *s,
+            None => 0,
+        }
+    }
+
     fn match_current_operator(&mut self, token: char) -> bool {
         match self.token_vector[self.current_token] {
             tokens::TokenTypes::Operator(s) if s == token => true,
@@ -868,30 +2082,17 @@ impl Parser {
     }
 
     fn parse_loop_expressions<'a>(&mut self) -> Result<Expression, &'a str> {
-        let mut result_op: Expression;
         self.advance_tokens();
-        let mut left_op;
+        let left_op;
         let op = self.parse_prefix_expressions();
         match op {
             Ok(s) => left_op = s,
             Err(e) => return Err(e),
         }
         self.advance_tokens();
-        if &self.token_vector[self.current_token] == &tokens::TokenTypes::Semicolon {
-            return Ok(left_op);
-        }
-        loop {
-            if self.token_vector.len() <= self.next_token {
-                return Err("token overflow on return parse");
-            }
-            if &tokens::TokenTypes::EndOfLine == &self.token_vector[self.next_token] {
-                return Err("reached end of line without semicolon on return parse");
-            }
-            result_op = self.infix_expression_parser(0, left_op)?;
-            if &self.token_vector[self.current_token] == &tokens::TokenTypes::Semicolon {
-                break;
-            }
-            left_op = result_op;
+        let result_op = self.infix_expression_parser(0, left_op)?;
+        if &self.token_vector[self.current_token] != &tokens::TokenTypes::Semicolon {
+            return Err("expected a ; (semicolon) at the end of the expression");
         }
         return Ok(result_op);
     }
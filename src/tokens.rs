@@ -10,7 +10,15 @@ pub enum TokenTypes {
     Strings(String),
     Delim(char),
     Compare(Comparison),
+    Shift(char),
     Bang,
+    And,
+    Or,
+    Pipe,
+    FatArrow,
+    Question,
+    Dot,
+    Ellipsis,
     Comma,
     Colon,
     Semicolon,
@@ -33,6 +41,19 @@ pub enum Keywords {
     Function,
     True,
     False,
+    Nil,
+    Match,
+    Super,
+    Is,
+    Enum,
+    Struct,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Import,
+    Export,
+    As,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -57,7 +78,15 @@ impl fmt::Display for TokenTypes {
             TokenTypes::Strings(s) => write!(f, "String {}", s),
             TokenTypes::Delim(s) => write!(f, "Delim {}", s),
             TokenTypes::Compare(s) => write!(f, "Compare {}", s),
+            TokenTypes::Shift(s) => write!(f, "Shift {0}{0}", s),
             TokenTypes::Bang => write!(f, "Bang"),
+            TokenTypes::And => write!(f, "And"),
+            TokenTypes::Or => write!(f, "Or"),
+            TokenTypes::Pipe => write!(f, "Pipe"),
+            TokenTypes::FatArrow => write!(f, "Fat Arrow"),
+            TokenTypes::Question => write!(f, "Question"),
+            TokenTypes::Dot => write!(f, "Dot"),
+            TokenTypes::Ellipsis => write!(f, "Ellipsis"),
             TokenTypes::Comma => write!(f, "Comma"),
             TokenTypes::Colon => write!(f, "Colon"),
             TokenTypes::Semicolon => write!(f, "Semicolon"),
@@ -81,6 +110,19 @@ impl fmt::Display for Keywords {
             Keywords::Function => write!(f, "Function statement"),
             Keywords::True => write!(f, "True statement"),
             Keywords::False => write!(f, "False statement"),
+            Keywords::Nil => write!(f, "Nil statement"),
+            Keywords::Match => write!(f, "Match expression"),
+            Keywords::Super => write!(f, "Super expression"),
+            Keywords::Is => write!(f, "Is operator"),
+            Keywords::Enum => write!(f, "Enum declaration"),
+            Keywords::Struct => write!(f, "Struct declaration"),
+            Keywords::Throw => write!(f, "Throw statement"),
+            Keywords::Try => write!(f, "Try expression"),
+            Keywords::Catch => write!(f, "Catch clause"),
+            Keywords::Finally => write!(f, "Finally clause"),
+            Keywords::Import => write!(f, "Import statement"),
+            Keywords::Export => write!(f, "Export statement"),
+            Keywords::As => write!(f, "As clause"),
         }
     }
 }
@@ -96,4 +138,4 @@ impl fmt::Display for Comparison {
             Comparison::NotEqual => write!(f, "=="),
         }
     }
-}
\ No newline at end of file
+}